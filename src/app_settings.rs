@@ -1,6 +1,11 @@
-use std::{fs::File, io, path::PathBuf};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    io,
+    path::{Path, PathBuf},
+};
 
-use livesplit_core::HotkeyConfig;
+use livesplit_core::{HotkeyConfig, TimingMethod};
 use serde::{Deserialize, Serialize};
 use std::io::BufReader;
 use thiserror::Error;
@@ -10,6 +15,148 @@ pub struct Settings {
     pub hkc: HotkeyConfig,
     pub splits_path: Option<PathBuf>,
     pub layout_path: Option<PathBuf>,
+    /// Recently opened splits files, most recent first.
+    #[serde(default)]
+    pub recent_splits: Vec<RecentSplits>,
+    /// Recently opened layouts, most recent first.
+    #[serde(default)]
+    pub recent_layouts: Vec<PathBuf>,
+    /// Auto splitters, keyed by the splits file they belong to.
+    #[serde(default)]
+    pub auto_splitters: HashMap<PathBuf, AutoSplitterSettings>,
+    #[serde(default)]
+    pub compare_against: CompareAgainst,
+    #[serde(default)]
+    pub server: ServerSettings,
+    #[serde(default)]
+    pub websocket: WebSocketSettings,
+    #[serde(default)]
+    pub backup: BackupSettings,
+    #[serde(default)]
+    pub auto_save: AutoSaveSettings,
+}
+
+/// How many files each of the recent files lists remembers.
+const MAX_RECENT_FILES: usize = 10;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RecentSplits {
+    pub path: PathBuf,
+    /// The layout to load along with these splits.
+    #[serde(default)]
+    pub layout: Option<PathBuf>,
+}
+
+/// The timing method the timer starts out comparing against.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompareAgainst {
+    #[default]
+    RealTime,
+    GameTime,
+}
+
+impl From<CompareAgainst> for TimingMethod {
+    fn from(value: CompareAgainst) -> Self {
+        match value {
+            CompareAgainst::RealTime => Self::RealTime,
+            CompareAgainst::GameTime => Self::GameTime,
+        }
+    }
+}
+
+impl From<TimingMethod> for CompareAgainst {
+    fn from(value: TimingMethod) -> Self {
+        match value {
+            TimingMethod::RealTime => Self::RealTime,
+            TimingMethod::GameTime => Self::GameTime,
+        }
+    }
+}
+
+/// Settings for the LiveSplit Server compatible TCP server.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct ServerSettings {
+    pub enabled: bool,
+    pub port: u16,
+    /// Listen on every network interface instead of just this machine.
+    #[serde(default)]
+    pub allow_lan: bool,
+}
+
+impl Default for ServerSettings {
+    fn default() -> Self {
+        // the original LiveSplit Server's default port
+        Self {
+            enabled: false,
+            port: 16834,
+            allow_lan: false,
+        }
+    }
+}
+
+/// Settings for the WebSocket server used by browser overlays.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WebSocketSettings {
+    pub enabled: bool,
+    pub port: u16,
+    /// Listen on every network interface instead of just this machine.
+    #[serde(default)]
+    pub allow_lan: bool,
+    /// Web page origins allowed to connect besides the ones served from this machine, e.g. `https://example.com`.
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
+}
+
+impl Default for WebSocketSettings {
+    fn default() -> Self {
+        // one above the LiveSplit Server port so both can run side by side
+        Self {
+            enabled: false,
+            port: 16835,
+            allow_lan: false,
+            allowed_origins: Vec::new(),
+        }
+    }
+}
+
+/// When to save the splits file without asking.
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub struct AutoSaveSettings {
+    pub on_reset: bool,
+    pub on_personal_best: bool,
+}
+
+/// Where copies of the splits are kept in case the real file gets lost or broken.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BackupSettings {
+    /// Overrides the default backup directory.
+    pub directory: Option<PathBuf>,
+    /// How many backups to keep per splits file.
+    pub max_backups: usize,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            directory: None,
+            max_backups: 10,
+        }
+    }
+}
+
+impl BackupSettings {
+    pub fn directory(&self) -> Option<PathBuf> {
+        self.directory
+            .clone()
+            .or_else(|| Some(dirs::data_local_dir()?.join("livesplit").join("backups")))
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AutoSplitterSettings {
+    pub module_path: PathBuf,
+    #[serde(default)]
+    pub user_settings: BTreeMap<String, bool>,
 }
 
 #[derive(Debug, Error)]
@@ -23,21 +170,67 @@ pub enum SettingsSaveError {
 }
 
 impl Settings {
-    pub fn load() -> Option<Self> {
-        let mut settings_file_path = dirs::config_dir()?;
+    /// The auto splitter linked to the currently loaded splits file, if any.
+    pub fn auto_splitter(&self) -> Option<&AutoSplitterSettings> {
+        self.auto_splitters.get(self.splits_path.as_ref()?)
+    }
+
+    pub fn auto_splitter_mut(&mut self) -> Option<&mut AutoSplitterSettings> {
+        self.auto_splitters.get_mut(self.splits_path.as_ref()?)
+    }
 
-        settings_file_path.push("livesplit.json");
+    /// The layout linked to the currently loaded splits file, if any.
+    pub fn linked_layout(&self) -> Option<&PathBuf> {
+        let splits_path = self.splits_path.as_ref()?;
+        self.recent_splits
+            .iter()
+            .find(|recent| recent.path == *splits_path)?
+            .layout
+            .as_ref()
+    }
+
+    /// Moves `path` to the top of the recent splits, keeping whatever layout it was linked to.
+    pub fn add_recent_splits(&mut self, path: PathBuf) {
+        let layout = self
+            .recent_splits
+            .iter()
+            .position(|recent| recent.path == path)
+            .and_then(|index| self.recent_splits.remove(index).layout);
 
-        serde_json::from_reader(BufReader::new(File::open(settings_file_path).ok()?)).ok()
+        self.recent_splits.insert(0, RecentSplits { path, layout });
+        self.recent_splits.truncate(MAX_RECENT_FILES);
     }
 
-    pub fn save(&self) -> Result<(), SettingsSaveError> {
-        let mut settings_file_path =
-            dirs::config_dir().ok_or(SettingsSaveError::UnsupportedConfigDirectory)?;
+    /// Moves `path` to the top of the recent layouts and links it to the currently loaded splits.
+    pub fn add_recent_layout(&mut self, path: PathBuf) {
+        // the last layout used with a splits file is the one to bring back along with it
+        if let Some(splits_path) = &self.splits_path
+            && let Some(recent) = self
+                .recent_splits
+                .iter_mut()
+                .find(|recent| recent.path == *splits_path)
+        {
+            recent.layout = Some(path.clone());
+        }
+
+        self.recent_layouts.retain(|recent| *recent != path);
+        self.recent_layouts.insert(0, path);
+        self.recent_layouts.truncate(MAX_RECENT_FILES);
+    }
+
+    /// Where settings live unless the command line says otherwise.
+    pub fn default_path() -> Option<PathBuf> {
+        Some(dirs::config_dir()?.join("livesplit.json"))
+    }
+
+    pub fn load(path: &Path) -> Option<Self> {
+        serde_json::from_reader(BufReader::new(File::open(path).ok()?)).ok()
+    }
 
-        settings_file_path.push("livesplit.json");
+    pub fn save(&self, path: Option<&Path>) -> Result<(), SettingsSaveError> {
+        let path = path.ok_or(SettingsSaveError::UnsupportedConfigDirectory)?;
 
-        let writer = File::create(settings_file_path)?;
+        let writer = File::create(path)?;
 
         serde_json::to_writer(writer, self)?;
 
@@ -0,0 +1,310 @@
+//! Runs WebAssembly auto splitters against the shared timer.
+//!
+//! livesplit-core ships an `auto_splitting::Runtime`, but in the version we're on it always starts scripts with an
+//! empty settings store and never exposes the settings a script registers, so there would be no way to offer the
+//! user settings at all. This drives the same underlying `livesplit-auto-splitting` runtime ourselves instead, on
+//! its own thread, the same way livesplit-core does.
+
+use std::{
+    collections::BTreeMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        mpsc::{self, RecvTimeoutError},
+    },
+    thread,
+    time::Instant,
+};
+
+use livesplit_auto_splitting::{
+    CreationError, Runtime, SettingValue, SettingsStore, Timer as AutoSplitTimer, TimerState,
+};
+use livesplit_core::{SharedTimer, TimerPhase};
+use thiserror::Error;
+
+use crate::livesplit_state::{ResetRequest, set_game_time_paused};
+
+#[derive(Clone, Debug, Default)]
+pub enum AutoSplitterStatus {
+    #[default]
+    NotLoaded,
+    Loading(PathBuf),
+    Running(PathBuf),
+    Failed(String),
+}
+
+/// A boolean setting the currently loaded auto splitter asked the user for.
+#[derive(Clone, Debug)]
+pub struct AutoSplitterSetting {
+    pub key: String,
+    pub description: String,
+    pub value: bool,
+}
+
+pub struct AutoSplitter {
+    sender: mpsc::Sender<Request>,
+    shared: Arc<Mutex<Shared>>,
+}
+
+#[derive(Default)]
+struct Shared {
+    status: AutoSplitterStatus,
+    user_settings: Vec<AutoSplitterSetting>,
+    /// The last message the script logged, shown next to the status to help with debugging scripts.
+    last_log: Option<String>,
+}
+
+enum Request {
+    Load(PathBuf, Vec<u8>, SettingsStore),
+    Unload,
+}
+
+#[derive(Debug, Error)]
+pub enum AutoSplitterError {
+    #[error("Failed to read auto splitter: {0}")]
+    ReadFailed(#[from] io::Error),
+    #[error("The auto splitter thread stopped unexpectedly")]
+    ThreadStopped,
+}
+
+impl AutoSplitter {
+    pub fn new(timer: SharedTimer, reset_request: ResetRequest) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let shared = Arc::new(Mutex::new(Shared::default()));
+
+        thread::Builder::new()
+            .name("Auto Splitting Runtime".into())
+            .spawn({
+                let shared = shared.clone();
+                move || run(&receiver, &timer, &reset_request, &shared)
+            })
+            .expect("Failed to spawn auto splitter thread");
+
+        Self { sender, shared }
+    }
+
+    /// Starts loading the module at `path` with the given user settings, replacing any loaded auto splitter.
+    /// Compiling the module happens in the background; watch `status` for the outcome.
+    pub fn load(
+        &self,
+        path: &Path,
+        user_settings: &BTreeMap<String, bool>,
+    ) -> Result<(), AutoSplitterError> {
+        let module = fs::read(path)?;
+
+        let mut store = SettingsStore::new();
+        for (key, value) in user_settings {
+            store.set(key.as_str().into(), SettingValue::Bool(*value));
+        }
+
+        self.shared().status = AutoSplitterStatus::Loading(path.to_owned());
+
+        self.sender
+            .send(Request::Load(path.to_owned(), module, store))
+            .map_err(|_| AutoSplitterError::ThreadStopped)
+    }
+
+    pub fn unload(&self) -> Result<(), AutoSplitterError> {
+        self.sender
+            .send(Request::Unload)
+            .map_err(|_| AutoSplitterError::ThreadStopped)
+    }
+
+    pub fn status(&self) -> AutoSplitterStatus {
+        self.shared().status.clone()
+    }
+
+    pub fn user_settings(&self) -> Vec<AutoSplitterSetting> {
+        self.shared().user_settings.clone()
+    }
+
+    pub fn last_log(&self) -> Option<String> {
+        self.shared().last_log.clone()
+    }
+
+    fn shared(&self) -> std::sync::MutexGuard<'_, Shared> {
+        // the runtime thread never panics while holding this lock
+        self.shared.lock().expect("Auto splitter lock poisoned!")
+    }
+}
+
+fn run(
+    receiver: &mpsc::Receiver<Request>,
+    timer: &SharedTimer,
+    reset_request: &ResetRequest,
+    shared: &Arc<Mutex<Shared>>,
+) {
+    let set_shared = |status, user_settings| {
+        let mut shared = shared.lock().expect("Auto splitter lock poisoned!");
+        shared.status = status;
+        shared.user_settings = user_settings;
+        shared.last_log = None;
+    };
+
+    let load = |path: PathBuf, module: &[u8], store| match Runtime::new(
+        module,
+        Timer {
+            timer: timer.clone(),
+            reset_request: reset_request.clone(),
+            shared: shared.clone(),
+        },
+        store,
+    ) {
+        Ok(runtime) => {
+            set_shared(AutoSplitterStatus::Running(path), vec![]);
+            Some(runtime)
+        }
+        Err(e) => {
+            set_shared(AutoSplitterStatus::Failed(creation_error(&e)), vec![]);
+            None
+        }
+    };
+
+    let mut runtime = None;
+    let mut next_step = Instant::now();
+
+    loop {
+        let request = match &runtime {
+            Some(_) => receiver.recv_timeout(next_step.saturating_duration_since(Instant::now())),
+            None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+
+        match request {
+            Ok(Request::Load(path, module, store)) => {
+                runtime = load(path, &module, store);
+                next_step = Instant::now();
+            }
+            Ok(Request::Unload) => {
+                runtime = None;
+                set_shared(AutoSplitterStatus::NotLoaded, vec![]);
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                let Some(rt) = runtime.as_mut() else {
+                    continue;
+                };
+
+                match rt.update() {
+                    Ok(tick_rate) => {
+                        next_step += tick_rate;
+                        publish_user_settings(rt, shared);
+                    }
+                    Err(e) => {
+                        runtime = None;
+                        set_shared(AutoSplitterStatus::Failed(format!("{e:?}")), vec![]);
+                    }
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+    }
+}
+
+// Scripts register their settings as they run, so keep the list the UI sees in sync after every tick.
+fn publish_user_settings(runtime: &Runtime<Timer>, shared: &Mutex<Shared>) {
+    let registered = runtime.user_settings();
+
+    let mut shared = shared.lock().expect("Auto splitter lock poisoned!");
+    if shared.user_settings.len() == registered.len() {
+        return;
+    }
+
+    shared.user_settings = registered
+        .iter()
+        .map(|setting| {
+            let value = runtime
+                .settings_store()
+                .get(&setting.key)
+                .unwrap_or(&setting.default_value);
+
+            AutoSplitterSetting {
+                key: setting.key.to_string(),
+                description: setting.description.to_string(),
+                value: match value {
+                    SettingValue::Bool(b) => *b,
+                    _ => false,
+                },
+            }
+        })
+        .collect();
+}
+
+fn creation_error(e: &CreationError) -> String {
+    // the creation errors only carry their useful detail in the source chain
+    let mut message = e.to_string();
+    let mut source = std::error::Error::source(e);
+    while let Some(inner) = source {
+        message.push_str(": ");
+        message.push_str(&inner.to_string());
+        source = inner.source();
+    }
+    message
+}
+
+// This wrapper is required because `SharedTimer` is an Arc<RwLock<T>>, so we can't implement the trait on it.
+struct Timer {
+    timer: SharedTimer,
+    /// Resets go through the app like the reset hotkey, so it can ask whether to update the splits.
+    reset_request: ResetRequest,
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl AutoSplitTimer for Timer {
+    fn state(&self) -> TimerState {
+        match self
+            .timer
+            .read()
+            .expect("Timer lock poisoned!")
+            .current_phase()
+        {
+            TimerPhase::NotRunning => TimerState::NotRunning,
+            TimerPhase::Running => TimerState::Running,
+            TimerPhase::Paused => TimerState::Paused,
+            TimerPhase::Ended => TimerState::Ended,
+        }
+    }
+
+    fn start(&mut self) {
+        self.timer.write().expect("Timer lock poisoned!").start();
+    }
+
+    fn split(&mut self) {
+        self.timer.write().expect("Timer lock poisoned!").split();
+    }
+
+    fn reset(&mut self) {
+        self.reset_request.request();
+    }
+
+    fn set_game_time(&mut self, time: livesplit_auto_splitting::time::Duration) {
+        self.timer
+            .write()
+            .expect("Timer lock poisoned!")
+            .set_game_time(time.into());
+    }
+
+    fn pause_game_time(&mut self) {
+        set_game_time_paused(&mut self.timer.write().expect("Timer lock poisoned!"), true);
+    }
+
+    fn resume_game_time(&mut self) {
+        set_game_time_paused(
+            &mut self.timer.write().expect("Timer lock poisoned!"),
+            false,
+        );
+    }
+
+    fn set_variable(&mut self, name: &str, value: &str) {
+        self.timer
+            .write()
+            .expect("Timer lock poisoned!")
+            .set_custom_variable(name, value);
+    }
+
+    fn log(&mut self, message: fmt::Arguments<'_>) {
+        self.shared
+            .lock()
+            .expect("Auto splitter lock poisoned!")
+            .last_log = Some(message.to_string());
+    }
+}
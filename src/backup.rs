@@ -0,0 +1,164 @@
+//! Keeping splits safe from crashes: atomic writes, rotating backups and a recovery file.
+
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use livesplit_core::AtomicDateTime;
+
+use crate::app_settings::BackupSettings;
+
+const RECOVERY_FILE: &str = "recovery.lss";
+/// Remembers which splits file the recovery file was taken from.
+const RECOVERY_PATH_FILE: &str = "recovery.path";
+
+/// Splits left behind by a session that didn't shut down cleanly.
+pub struct Recovery {
+    pub file: PathBuf,
+    pub splits_path: Option<PathBuf>,
+}
+
+/// Writes `contents` to a temporary file next to `path` and renames it into place, so a crash part way through
+/// leaves the old file intact instead of a truncated one.
+pub fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    // stay in the same directory so the rename never has to cross filesystems
+    let mut temp_path = OsString::from(path.as_os_str());
+    temp_path.push(".tmp");
+    let temp_path = PathBuf::from(temp_path);
+
+    let mut file = File::create(&temp_path)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+
+    fs::rename(&temp_path, path).inspect_err(|_| {
+        fs::remove_file(&temp_path).ok();
+    })
+}
+
+/// Where the backups of one splits file go. Every splits file gets its own directory, named after the file and a
+/// hash of its full path, so files with the same name in different places don't rotate each other's backups away.
+pub fn splits_backup_directory(settings: &BackupSettings, splits_path: &Path) -> Option<PathBuf> {
+    let stem = splits_path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy();
+
+    // FNV-1a rather than std's hasher, whose output isn't guaranteed to stay the same between Rust versions
+    let absolute_path = fs::canonicalize(splits_path).unwrap_or_else(|_| splits_path.to_owned());
+    let hash = absolute_path
+        .as_os_str()
+        .as_encoded_bytes()
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+        });
+
+    Some(settings.directory()?.join(format!("{stem}-{hash:016x}")))
+}
+
+/// Copies a freshly saved splits file into its backup directory, then deletes its oldest backups until only
+/// `max_backups` are left.
+pub fn back_up_splits(settings: &BackupSettings, splits_path: &Path) -> io::Result<()> {
+    let Some(directory) = splits_backup_directory(settings, splits_path) else {
+        return Ok(());
+    };
+    fs::create_dir_all(&directory)?;
+
+    let stem = splits_path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+
+    let now = AtomicDateTime::now().time;
+    // sorting these by name sorts them by age
+    let timestamp = format!(
+        "{}-{:02}-{:02}_{:02}-{:02}-{:02}-{:03}",
+        now.year(),
+        now.month() as u8,
+        now.day(),
+        now.hour(),
+        now.minute(),
+        now.second(),
+        now.millisecond()
+    );
+    // '_' sorts after '.', so saves within the same millisecond still come out in order
+    let mut backup_path = directory.join(format!("{stem}.{timestamp}.lss"));
+    for counter in 1.. {
+        if !backup_path.exists() {
+            break;
+        }
+        backup_path = directory.join(format!("{stem}.{timestamp}_{counter}.lss"));
+    }
+    fs::copy(splits_path, backup_path)?;
+
+    let mut backups: Vec<PathBuf> = fs::read_dir(&directory)?
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "lss"))
+        .collect();
+    backups.sort();
+
+    let excess = backups.len().saturating_sub(settings.max_backups);
+    for backup in &backups[..excess] {
+        fs::remove_file(backup)?;
+    }
+
+    Ok(())
+}
+
+/// Saves the run somewhere it survives a crash, without touching the splits file itself.
+pub fn write_recovery(
+    settings: &BackupSettings,
+    contents: &str,
+    splits_path: Option<&Path>,
+) -> io::Result<()> {
+    let Some(directory) = settings.directory() else {
+        return Ok(());
+    };
+    fs::create_dir_all(&directory)?;
+
+    write_atomically(&directory.join(RECOVERY_FILE), contents.as_bytes())?;
+    match splits_path {
+        Some(path) => write_atomically(
+            &directory.join(RECOVERY_PATH_FILE),
+            path.to_string_lossy().as_bytes(),
+        ),
+        None => remove_if_exists(&directory.join(RECOVERY_PATH_FILE)),
+    }
+}
+
+/// The recovery file, if the last session left one behind.
+pub fn recovery(settings: &BackupSettings) -> Option<Recovery> {
+    let directory = settings.directory()?;
+    let file = directory.join(RECOVERY_FILE);
+    if !file.is_file() {
+        return None;
+    }
+
+    let splits_path = fs::read_to_string(directory.join(RECOVERY_PATH_FILE))
+        .ok()
+        .map(PathBuf::from);
+
+    Some(Recovery { file, splits_path })
+}
+
+/// Deletes the recovery file, once everything in it is either saved or deliberately thrown away.
+pub fn clear_recovery(settings: &BackupSettings) -> io::Result<()> {
+    let Some(directory) = settings.directory() else {
+        return Ok(());
+    };
+
+    remove_if_exists(&directory.join(RECOVERY_FILE))?;
+    remove_if_exists(&directory.join(RECOVERY_PATH_FILE))
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
@@ -0,0 +1,85 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use livesplit_core::TimeSpan;
+
+/// A speedrun timer built on livesplit-core.
+#[derive(Parser)]
+#[command(version, about, args_conflicts_with_subcommands = true)]
+pub struct Args {
+    /// Work on splits files without opening the timer.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// The splits file to open.
+    pub splits: Option<PathBuf>,
+
+    /// The layout file to open. Takes priority over the layout linked to the splits.
+    #[arg(long)]
+    pub layout: Option<PathBuf>,
+
+    /// Read and write settings from this file instead of the default location, e.g. to keep separate profiles.
+    #[arg(long, value_name = "FILE")]
+    pub settings: Option<PathBuf>,
+
+    /// Don't register any hotkeys.
+    #[arg(long)]
+    pub no_hotkeys: bool,
+
+    /// Start with default settings. They replace the saved ones when the timer closes.
+    #[arg(long)]
+    pub reset_settings: bool,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Convert splits from any supported timer into a LiveSplit splits file.
+    Convert {
+        input: PathBuf,
+
+        /// Where to write the LiveSplit splits. Defaults to the input with an .lss extension.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Print the game, category, attempt count, personal best and sum of best of splits files.
+    Info {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Check that splits files can be read. Exits with an error if any of them can't.
+    Validate {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Rewrite LiveSplit splits files in place, fixing up anything inconsistent in them.
+    Fix {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Render a layout with splits to a PNG image, e.g. to preview layout changes.
+    Render {
+        splits: PathBuf,
+
+        /// The layout to render. Defaults to the standard layout.
+        #[arg(long)]
+        layout: Option<PathBuf>,
+
+        /// Where to write the image.
+        #[arg(short, long)]
+        output: PathBuf,
+
+        #[arg(long, default_value_t = 300)]
+        width: u32,
+
+        #[arg(long, default_value_t = 500)]
+        height: u32,
+
+        /// Start the timer and split up to this segment, using the personal best's split times.
+        #[arg(long, value_name = "INDEX")]
+        split_index: Option<usize>,
+
+        /// What the timer shows, e.g. 1:23.45. Starts the timer if --split-index isn't given.
+        #[arg(long, value_name = "TIME")]
+        time: Option<TimeSpan>,
+    },
+}
@@ -0,0 +1,257 @@
+//! The subcommands that work on splits files without opening a window. They read and write splits the same way the
+//! timer does.
+
+use std::{
+    fs::{self, File},
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, anyhow, bail};
+use livesplit_core::{
+    Layout, Run, Time, TimeSpan, Timer, TimingMethod,
+    analysis::sum_of_segments,
+    rendering::software::Renderer,
+    run::parser::TimerKind,
+    timing::formatter::{Regular, TimeFormatter},
+};
+
+use crate::{
+    backup,
+    cli::Command,
+    livesplit_state::{parse_layout, parse_splits, splits_to_string},
+};
+
+/// Runs `command`, returning the process exit code.
+pub fn run(command: Command) -> i32 {
+    let results: Vec<anyhow::Result<()>> = match command {
+        Command::Convert { input, output } => vec![convert(&input, output)],
+        Command::Info { files } => files.iter().map(|path| info(path)).collect(),
+        Command::Validate { files } => files.iter().map(|path| validate(path)).collect(),
+        Command::Fix { files } => files.iter().map(|path| fix(path)).collect(),
+        Command::Render {
+            splits,
+            layout,
+            output,
+            width,
+            height,
+            split_index,
+            time,
+        } => vec![render(
+            &splits,
+            layout.as_deref(),
+            &output,
+            [width, height],
+            split_index,
+            time,
+        )],
+    };
+
+    let mut failed = false;
+    for e in results.into_iter().filter_map(Result::err) {
+        eprintln!("error: {e:#}");
+        failed = true;
+    }
+
+    i32::from(failed)
+}
+
+fn convert(input: &Path, output: Option<PathBuf>) -> anyhow::Result<()> {
+    let output = output.unwrap_or_else(|| input.with_extension("lss"));
+    // the original file is kept no matter what format it's in, fix is for rewriting LiveSplit splits in place
+    if output == input
+        || fs::canonicalize(&output)
+            .is_ok_and(|output| fs::canonicalize(input).ok() == Some(output))
+    {
+        bail!(
+            "{}: converting would overwrite the input, choose a different file with --output",
+            input.display()
+        );
+    }
+
+    let parsed = parse_splits(input).with_context(|| format!("{}", input.display()))?;
+    write_splits(parsed.run, &output).with_context(|| format!("{}", output.display()))?;
+
+    println!(
+        "{}: converted from {} to {}",
+        input.display(),
+        parsed.kind,
+        output.display()
+    );
+    Ok(())
+}
+
+fn info(path: &Path) -> anyhow::Result<()> {
+    let parsed = parse_splits(path).with_context(|| format!("{}", path.display()))?;
+    let run = &parsed.run;
+
+    let personal_best = |method| {
+        run.segments()
+            .last()
+            .and_then(|segment| segment.personal_best_split_time()[method])
+    };
+    let sum_of_best =
+        |method| sum_of_segments::calculate_best(run.segments(), false, false, method);
+
+    println!("{}", path.display());
+    println!("  Format:        {}", parsed.kind);
+    println!("  Game:          {}", run.game_name());
+    println!("  Category:      {}", run.category_name());
+    println!("  Segments:      {}", run.len());
+    println!("  Attempts:      {}", run.attempt_count());
+    println!("  Personal Best: {}", format_times(personal_best));
+    println!("  Sum of Best:   {}", format_times(sum_of_best));
+    Ok(())
+}
+
+fn validate(path: &Path) -> anyhow::Result<()> {
+    let parsed = parse_splits(path).with_context(|| format!("{}", path.display()))?;
+    if parsed.run.is_empty() {
+        bail!("{}: the splits have no segments", path.display());
+    }
+
+    println!("{}: ok ({})", path.display(), parsed.kind);
+    Ok(())
+}
+
+fn fix(path: &Path) -> anyhow::Result<()> {
+    let parsed = parse_splits(path).with_context(|| format!("{}", path.display()))?;
+
+    // never overwrite another timer's file with LiveSplit splits
+    if parsed.kind != TimerKind::LiveSplit {
+        bail!(
+            "{}: this is a {} file, use convert to turn it into LiveSplit splits",
+            path.display(),
+            parsed.kind
+        );
+    }
+
+    write_splits(parsed.run, path).with_context(|| format!("{}", path.display()))?;
+
+    println!("{}: fixed", path.display());
+    Ok(())
+}
+
+fn render(
+    splits: &Path,
+    layout: Option<&Path>,
+    output: &Path,
+    [width, height]: [u32; 2],
+    split_index: Option<usize>,
+    time: Option<TimeSpan>,
+) -> anyhow::Result<()> {
+    if width == 0 || height == 0 {
+        bail!("the image needs a width and height of at least 1");
+    }
+
+    let parsed = parse_splits(splits).with_context(|| format!("{}", splits.display()))?;
+    let mut layout = match layout {
+        Some(path) => parse_layout(path).with_context(|| format!("{}", path.display()))?,
+        None => Layout::default_layout(),
+    };
+
+    let mut run = parsed.run;
+    if split_index.is_some() || time.is_some() {
+        mirror_real_time(&mut run);
+        // jump_to starting the attempt counts it, and the render should show the count from the file
+        run.set_attempt_count(run.attempt_count().saturating_sub(1));
+    }
+
+    let mut timer = Timer::new(run).map_err(|_| anyhow!("the splits have no segments"))?;
+    if split_index.is_some() || time.is_some() {
+        jump_to(&mut timer, split_index.unwrap_or(0), time)?;
+    }
+
+    let state = layout.state(&timer.snapshot());
+    let mut renderer = Renderer::new();
+    renderer.render(&state, [width, height]);
+
+    write_png(output, renderer.image_data(), [width, height])
+        .with_context(|| format!("{}", output.display()))?;
+
+    println!("{}: rendered {width}x{height}", output.display());
+    Ok(())
+}
+
+/// Puts the timer `split_index` splits into a run, showing `time`. Real time can't be set from outside the timer,
+/// so this drives a paused game time instead and has the layout show that.
+///
+/// Starting the run counts an attempt like it would in the app, so callers that want the attempt count from the
+/// file need to take one off the run before handing it to the timer. Runs without any attempts will still show one.
+fn jump_to(timer: &mut Timer, split_index: usize, time: Option<TimeSpan>) -> anyhow::Result<()> {
+    let segment_count = timer.run().len();
+    if split_index > segment_count {
+        bail!("--split-index is {split_index}, but the splits only have {segment_count} segments");
+    }
+
+    timer.set_current_timing_method(TimingMethod::GameTime);
+    timer.start();
+    timer.initialize_game_time();
+    timer.pause_game_time();
+
+    let mut current = TimeSpan::zero();
+    for index in 0..split_index {
+        let personal_best = timer.run().segment(index).personal_best_split_time();
+        // keep the last time for segments without one, so the splits never go backwards
+        if let Some(split_time) = personal_best.game_time.or(personal_best.real_time) {
+            current = split_time;
+        }
+        timer.set_game_time(current);
+        timer.split();
+    }
+
+    timer.set_game_time(time.unwrap_or(current));
+    Ok(())
+}
+
+/// `jump_to` can only drive game time, so splits timed in real time would have nothing to compare against. Copy
+/// their real times over to game time when there are no game times at all.
+fn mirror_real_time(run: &mut Run) {
+    let has_game_time = run
+        .segments()
+        .iter()
+        .any(|segment| segment.personal_best_split_time().game_time.is_some());
+    if has_game_time {
+        return;
+    }
+
+    let mirror = |time: &mut Time| time.game_time = time.real_time;
+    for segment in run.segments_mut() {
+        segment
+            .comparisons_mut()
+            .iter_mut()
+            .for_each(|(_, time)| mirror(time));
+        mirror(segment.best_segment_time_mut());
+        segment
+            .segment_history_mut()
+            .iter_mut()
+            .for_each(|(_, time)| mirror(time));
+    }
+}
+
+fn write_png(path: &Path, rgba: &[u8], [width, height]: [u32; 2]) -> anyhow::Result<()> {
+    let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(rgba)?;
+    writer.finish()?;
+    Ok(())
+}
+
+fn write_splits(run: Run, path: &Path) -> anyhow::Result<()> {
+    let timer = Timer::new(run).map_err(|_| anyhow!("the splits have no segments"))?;
+    let s = splits_to_string(&timer)?;
+
+    backup::write_atomically(path, s.as_bytes())?;
+    Ok(())
+}
+
+fn format_times(time: impl Fn(TimingMethod) -> Option<TimeSpan>) -> String {
+    format!(
+        "{} (Real Time), {} (Game Time)",
+        Regular::new().format(time(TimingMethod::RealTime)),
+        Regular::new().format(time(TimingMethod::GameTime))
+    )
+}
@@ -2,6 +2,29 @@ use iced::keyboard::{
     Modifiers,
     key::{Code, Physical},
 };
+use livesplit_core::{HotkeyConfig, hotkey::Hotkey};
+
+use crate::Message;
+
+/// Maps a pressed hotkey to the timer action it is bound to, mirroring what livesplit-core's `HotkeySystem` does
+/// for global hotkeys.
+pub fn hotkey_to_message(config: &HotkeyConfig, hotkey: Hotkey) -> Option<Message> {
+    let bindings = [
+        (config.split, Message::StartOrSplit),
+        (config.reset, Message::Reset),
+        (config.undo, Message::UndoSplit),
+        (config.skip, Message::SkipSplit),
+        (config.pause, Message::TogglePause),
+        (config.undo_all_pauses, Message::UndoAllPauses),
+        (config.previous_comparison, Message::PreviousComparison),
+        (config.next_comparison, Message::NextComparison),
+        (config.toggle_timing_method, Message::ToggleTimingMethod),
+    ];
+
+    bindings
+        .into_iter()
+        .find_map(|(binding, message)| (binding == Some(hotkey)).then_some(message))
+}
 
 pub fn iced_key_to_livesplit_hotkey(
     key: Physical,
@@ -1,24 +1,134 @@
 use core::fmt;
 use std::{
+    ffi::OsStr,
     fs::{self, File},
-    io::{self, BufReader, Read, Seek, SeekFrom, Write},
+    io::{self, BufReader, Read, Seek, SeekFrom},
     path::Path,
+    sync::{
+        Arc, RwLockWriteGuard,
+        atomic::{AtomicBool, Ordering},
+    },
 };
 
 use livesplit_core::{
-    HotkeySystem, Layout, Run, Segment, SharedTimer, Timer, TimerPhase,
+    HotkeyConfig, HotkeySystem, Layout, Run, Segment, SharedTimer, Time, TimeSpan, Timer,
+    TimerPhase, TimingMethod,
+    hotkey::{Hook, Hotkey},
     layout::{self, LayoutSettings, LayoutState},
     rendering::software::Renderer,
-    run::parser::composite,
+    run::parser::{
+        TimerKind,
+        composite::{self, ParsedRun},
+        face_split, flitter, livesplit, llanfair, llanfair_gered, portal2_live_timer, shit_split,
+        source_live_timer, speedrun_igt, splits_io, splitterino, splitterz, splitty,
+        time_split_tracker, urn, wsplit,
+    },
 };
 use thiserror::Error;
 
+use crate::{app_settings::BackupSettings, auto_splitter::AutoSplitter, backup};
+
+/// How the configured hotkeys reach the timer. Global hotkeys need a working platform backend (X11, evdev, ...),
+/// which is often missing on Wayland or in CI. When it can't start we keep the config ourselves and the app
+/// dispatches it from the main window's key events instead.
+pub enum HotkeyMode {
+    Global(HotkeySystem, ResetHotkey),
+    WindowLocal {
+        config: HotkeyConfig,
+        active: bool,
+        reason: String,
+    },
+    /// Turned off from the command line. The config is still kept so it can be edited and saved.
+    Disabled(HotkeyConfig),
+}
+
+/// Lets the parts of the app that can't send it messages (the global reset hotkey, the servers, the auto splitter)
+/// ask for a reset. The app picks it up on the next tick, so it can ask whether to update the splits first.
+#[derive(Clone, Default)]
+pub struct ResetRequest(Arc<AtomicBool>);
+
+impl ResetRequest {
+    pub fn request(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    fn take(&self) -> bool {
+        self.0.swap(false, Ordering::Relaxed)
+    }
+}
+
+/// The global reset hotkey. It's kept out of the `HotkeySystem`, which would reset the timer straight away, so that
+/// resets go through the app and it can ask whether to update the splits first.
+pub struct ResetHotkey {
+    hook: Hook,
+    hotkey: Option<Hotkey>,
+    active: bool,
+    pressed: ResetRequest,
+}
+
+impl ResetHotkey {
+    fn new(
+        hotkey: Option<Hotkey>,
+        pressed: ResetRequest,
+    ) -> Result<Self, livesplit_core::hotkey::Error> {
+        let mut me = Self {
+            hook: Hook::new()?,
+            hotkey,
+            active: false,
+            pressed,
+        };
+        me.activate()?;
+
+        Ok(me)
+    }
+
+    fn set(&mut self, hotkey: Option<Hotkey>) -> Result<(), livesplit_core::hotkey::Error> {
+        if hotkey == self.hotkey {
+            return Ok(());
+        }
+        let active = self.active;
+        self.deactivate()?;
+        self.hotkey = hotkey;
+        if active {
+            self.activate()?;
+        }
+
+        Ok(())
+    }
+
+    fn activate(&mut self) -> Result<(), livesplit_core::hotkey::Error> {
+        if !self.active
+            && let Some(hotkey) = self.hotkey
+        {
+            let pressed = self.pressed.clone();
+            self.hook.register(hotkey, move || pressed.request())?;
+        }
+        self.active = true;
+
+        Ok(())
+    }
+
+    fn deactivate(&mut self) -> Result<(), livesplit_core::hotkey::Error> {
+        if self.active
+            && let Some(hotkey) = self.hotkey
+        {
+            self.hook.unregister(hotkey)?;
+        }
+        self.active = false;
+
+        Ok(())
+    }
+}
+
 pub struct LivesplitState {
     pub renderer: Renderer,
     layout: Layout,
+    layout_dirty: bool,
     pub(crate) timer: SharedTimer,
+    pub auto_splitter: AutoSplitter,
+    reset_request: ResetRequest,
     layout_state: LayoutState,
-    pub hks: HotkeySystem,
+    hotkeys: HotkeyMode,
 
     last_rendered_width: u32,
     last_rendered_height: u32,
@@ -35,14 +145,26 @@ impl LivesplitState {
     }
 
     pub fn update(&mut self, width: u32, height: u32) {
+        {
+            let mut timer = self.timer_mut();
+            // Game time starts every attempt uninitialized, which leaves it blank unless an auto splitter sets it.
+            // When comparing against it, have it follow real time instead so loads can still be removed by pausing
+            // it by hand. Otherwise leave it alone, or runs that never use game time get real time saved as game time.
+            if timer.current_phase().is_running()
+                && !timer.is_game_time_initialized()
+                && timer.current_timing_method() == TimingMethod::GameTime
+            {
+                timer.initialize_game_time();
+            }
+        }
+
         if width == 0 || height == 0 {
             return;
         }
         self.layout.update_state(
             &mut self.layout_state,
-            // SAFETY: there are only two threads in this program - ours and the hotkey system.
-            // Theoretically the hotkey system *could* panic mid-run, but that is
-            // so unlikely that I don't care to recover from it.
+            // The lock is only poisoned if another thread touching the timer (the hotkey system, the servers, the
+            // auto splitter) panicked mid-update, which is so unlikely that I don't care to recover from it.
             &self.timer.read().expect("Timer lock poisoned!").snapshot(),
         );
 
@@ -51,35 +173,104 @@ impl LivesplitState {
         self.renderer.render(&self.layout_state, [width, height]);
     }
 
-    pub fn load_splits(&mut self, path: &Path) -> Result<(), LoadSplitsError> {
+    /// Loads splits in any format livesplit-core can import, returning the format they were in.
+    pub fn load_splits(&mut self, path: &Path) -> Result<TimerKind<'static>, LoadSplitsError> {
+        let mut timer = self.timer.write().expect("Timer lock poisoned!");
+        if timer.current_phase().is_running() || timer.current_phase().is_paused() {
+            return Err(LoadSplitsError::TimerRunning);
+        }
+
+        let ParsedRun { mut run, kind } = parse_splits(path)?;
+        // splits from other timers aren't saved anywhere we can write to yet
+        if kind != TimerKind::LiveSplit {
+            run.mark_as_modified();
+        }
+        timer
+            .replace_run(run, true)
+            .map_err(|_| LoadSplitsError::NoSegments)?;
+
+        Ok(kind)
+    }
+
+    pub fn save_splits(&self, path: &Path) -> Result<(), SaveSplitsError> {
+        let s = self.splits_to_string()?;
+
+        backup::write_atomically(path, s.as_bytes())?;
+
+        // only once it's safely on disk, otherwise a failed save would stop us asking to save on quit
+        self.timer_mut().mark_as_unmodified();
+
+        Ok(())
+    }
+
+    /// Writes the splits to the recovery file, leaving them marked as unsaved.
+    pub fn save_recovery(
+        &self,
+        settings: &BackupSettings,
+        splits_path: Option<&Path>,
+    ) -> Result<(), SaveSplitsError> {
+        let s = self.splits_to_string()?;
+
+        backup::write_recovery(settings, &s, splits_path)?;
+
+        Ok(())
+    }
+
+    fn splits_to_string(&self) -> Result<String, fmt::Error> {
+        splits_to_string(&self.timer.read().expect("Timer lock poisoned!"))
+    }
+
+    /// Loads splits from a backup in place of the current ones. They stay marked as unsaved so they end up back in
+    /// the real splits file.
+    pub fn restore_splits(&mut self, backup: &Path) -> Result<(), LoadSplitsError> {
         let mut timer = self.timer.write().expect("Timer lock poisoned!");
         if !timer.current_phase().is_running() && !timer.current_phase().is_paused() {
-            let run_bytes = fs::read(path)?;
-            let run = composite::parse_and_fix(&run_bytes, path.parent())
-                .map_err(|_| LoadSplitsError::ParseError)?;
+            let mut run = parse_splits(backup)?.run;
+            run.mark_as_modified();
             timer
-                .replace_run(run.run, true)
-                .map_err(|_| LoadSplitsError::ParseError)?;
+                .replace_run(run, true)
+                .map_err(|_| LoadSplitsError::NoSegments)?;
         }
 
         Ok(())
     }
 
-    pub fn save_splits(&self, path: &Path) -> Result<(), SaveSplitsError> {
-        let mut timer = self.timer.write().expect("Timer lock poisoned!");
+    /// Applies `edit` to a copy of the run and swaps it in, marking the splits as modified. Swapping the run resets
+    /// the timer without updating the splits, so this refuses to do anything until the last attempt has been reset.
+    pub fn edit_run(&self, edit: impl FnOnce(&mut Run)) -> Result<(), EditRunError> {
+        let mut timer = self.timer_mut();
+        if timer.current_phase() != TimerPhase::NotRunning {
+            return Err(EditRunError::TimerRunning);
+        }
 
-        timer.mark_as_unmodified();
+        let mut run = timer.run().clone();
+        edit(&mut run);
+        run.mark_as_modified();
 
-        // this is stupid but sadly necessary because of bad API design
-        let mut s = String::new();
+        timer
+            .replace_run(run, false)
+            .map_err(|_| EditRunError::EmptyRun)?;
 
-        livesplit_core::run::saver::livesplit::save_timer(&timer, &mut s)?;
+        Ok(())
+    }
 
-        drop(timer);
+    // see `update` for why a poisoned lock isn't recovered from
+    pub fn timer_mut(&self) -> RwLockWriteGuard<'_, Timer> {
+        self.timer.write().expect("Timer lock poisoned!")
+    }
 
-        File::create(path)?.write_all(s.as_bytes())?;
+    pub fn is_game_time_paused(&self) -> bool {
+        self.timer
+            .read()
+            .expect("Timer lock poisoned!")
+            .is_game_time_paused()
+    }
 
-        Ok(())
+    pub fn current_timing_method(&self) -> TimingMethod {
+        self.timer
+            .read()
+            .expect("Timer lock poisoned!")
+            .current_timing_method()
     }
 
     pub fn is_dirty(&self) -> bool {
@@ -91,24 +282,99 @@ impl LivesplitState {
     }
 
     pub fn load_layout(&mut self, path: &std::path::Path) -> Result<(), LoadLayoutError> {
-        let mut reader = BufReader::new(File::open(path)?);
-        let settings = LayoutSettings::from_json(&mut reader);
+        self.layout = parse_layout(path)?;
+        self.layout_dirty = false;
 
-        let layout = if let Ok(settings) = settings {
-            Layout::from_settings(settings)
-        } else {
-            reader.seek(SeekFrom::Start(0))?;
-            let mut buf = String::new();
-            reader.read_to_string(&mut buf)?;
+        Ok(())
+    }
 
-            layout::parser::parse(&buf).map_err(|_| LoadLayoutError::ParseError)?
-        };
+    pub fn save_layout(&mut self, path: &Path) -> Result<(), SaveLayoutError> {
+        let mut buf = Vec::new();
 
-        self.layout = layout;
+        self.layout.settings().write_json(&mut buf)?;
+
+        backup::write_atomically(path, &buf)?;
+
+        self.layout_dirty = false;
 
         Ok(())
     }
 
+    pub const fn is_layout_dirty(&self) -> bool {
+        self.layout_dirty
+    }
+
+    pub const fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
+    /// Replaces the layout with an edited one, marking it as needing to be saved.
+    pub fn set_layout(&mut self, layout: Layout) {
+        self.layout = layout;
+        self.layout_dirty = true;
+        self.layout_state = self
+            .layout
+            .state(&self.timer.read().expect("Timer lock poisoned!").snapshot());
+    }
+
+    /// The final time of the personal best, for both timing methods.
+    pub fn personal_best_time(&self) -> Time {
+        self.timer
+            .read()
+            .expect("Timer lock poisoned!")
+            .run()
+            .segments()
+            .last()
+            .map(|segment| segment.personal_best_split_time())
+            .unwrap_or_default()
+    }
+
+    /// Whether resetting now would have any new best segments or a new personal best to save, which LiveSplit asks
+    /// about before throwing them away.
+    pub fn reset_has_new_bests(&self) -> bool {
+        let timer = self.timer.read().expect("Timer lock poisoned!");
+        let phase = timer.current_phase();
+        if phase == TimerPhase::NotRunning {
+            return false;
+        }
+
+        let segments = timer.run().segments();
+        [TimingMethod::RealTime, TimingMethod::GameTime]
+            .into_iter()
+            .any(|method| {
+                // a skipped split leaves the next segment time covering several segments, so it can't be a gold
+                let mut previous_split = Some(TimeSpan::zero());
+                let new_gold = segments.iter().any(|segment| {
+                    let split_time = segment.split_time()[method];
+                    let is_gold =
+                        previous_split
+                            .zip(split_time)
+                            .is_some_and(|(previous, split)| {
+                                segment.best_segment_time()[method]
+                                    .is_none_or(|best| split - previous < best)
+                            });
+                    previous_split = split_time;
+                    is_gold
+                });
+
+                let new_personal_best = phase == TimerPhase::Ended
+                    && segments.last().is_some_and(|last| {
+                        last.split_time()[method].is_some_and(|time| {
+                            last.personal_best_split_time()[method].is_none_or(|pb| time < pb)
+                        })
+                    });
+
+                new_gold || new_personal_best
+            })
+    }
+
+    pub fn current_phase(&self) -> TimerPhase {
+        self.timer
+            .read()
+            .expect("Timer lock poisoned!")
+            .current_phase()
+    }
+
     pub fn is_timer_mid_run(&self) -> bool {
         matches!(
             self.timer
@@ -119,7 +385,7 @@ impl LivesplitState {
         )
     }
 
-    pub fn with_settings(settings: &crate::app_settings::Settings) -> Self {
+    pub fn with_settings(settings: &crate::app_settings::Settings, hotkeys_enabled: bool) -> Self {
         let run = {
             let mut run = Run::new();
 
@@ -130,28 +396,52 @@ impl LivesplitState {
             run
         };
 
-        // SAFETY: we know this is safe because the run is guaranteed valid - we constructed it ourselves
-        let timer = Timer::new(run).unwrap();
+        let mut timer = Timer::new(run).expect("The default run has a segment");
+        timer.set_current_timing_method(settings.compare_against.into());
 
         let mut layout = Layout::default_layout();
 
         let layout_state = layout.state(&timer.snapshot());
         let timer = timer.into_shared();
+        let reset_request = ResetRequest::default();
 
-        // The hotkey system can fail to initialize if multiple keys are set the same in the configuration.
-        // Unfortunately, as the current system is designed upstream, we have no platform-independent way to
-        // detect this and tell the difference between that and an actual panic-worthy failure.
-        // Fortunately, in order for this to happen the user would have to manually edit the config file, and
-        // if they are doing that they are living in a state of sin and deserve what happens to them
-        let hks = HotkeySystem::with_config(timer.clone(), settings.hkc)
-            .expect("Failed to initialize hotkey system");
+        // The hotkey system can fail to initialize if multiple keys are set the same in the configuration, or if
+        // the platform has no usable global hotkey backend. Either way the user still needs some way to drive the
+        // timer, so fall back to hotkeys that only work while the main window is focused.
+        let hotkeys = if !hotkeys_enabled {
+            HotkeyMode::Disabled(settings.hkc)
+        } else {
+            match HotkeySystem::with_config(
+                timer.clone(),
+                HotkeyConfig {
+                    reset: None,
+                    ..settings.hkc
+                },
+            )
+            .and_then(|hks| {
+                Ok(HotkeyMode::Global(
+                    hks,
+                    ResetHotkey::new(settings.hkc.reset, reset_request.clone())?,
+                ))
+            }) {
+                Ok(hotkeys) => hotkeys,
+                Err(e) => HotkeyMode::WindowLocal {
+                    config: settings.hkc,
+                    active: true,
+                    reason: e.to_string(),
+                },
+            }
+        };
 
         let mut me = Self {
             renderer: Renderer::new(),
             layout,
+            layout_dirty: false,
+            auto_splitter: AutoSplitter::new(timer.clone(), reset_request.clone()),
             timer,
+            reset_request,
             layout_state,
-            hks,
+            hotkeys,
 
             last_rendered_width: 0,
             last_rendered_height: 0,
@@ -171,28 +461,302 @@ impl LivesplitState {
             me.load_splits(path).ok();
         });
 
+        settings.auto_splitter().inspect(|auto_splitter| {
+            me.auto_splitter
+                .load(&auto_splitter.module_path, &auto_splitter.user_settings)
+                .ok();
+        });
+
         me
     }
 
     pub const fn save_hotkeys_to_settings(&self, app_settings: &mut crate::app_settings::Settings) {
-        app_settings.hkc = self.hks.config();
+        app_settings.hkc = self.hotkey_config();
+    }
+
+    pub const fn hotkey_mode(&self) -> &HotkeyMode {
+        &self.hotkeys
+    }
+
+    pub const fn hotkey_config(&self) -> HotkeyConfig {
+        match &self.hotkeys {
+            HotkeyMode::Global(hks, reset) => HotkeyConfig {
+                reset: reset.hotkey,
+                ..hks.config()
+            },
+            HotkeyMode::WindowLocal { config, .. } | HotkeyMode::Disabled(config) => *config,
+        }
+    }
+
+    pub fn set_hotkey_config(
+        &mut self,
+        new_config: HotkeyConfig,
+    ) -> std::result::Result<(), livesplit_core::hotkey::Error> {
+        match &mut self.hotkeys {
+            HotkeyMode::Global(hks, reset) => {
+                hks.set_config(HotkeyConfig {
+                    reset: None,
+                    ..new_config
+                })?;
+                reset.set(new_config.reset)
+            }
+            HotkeyMode::WindowLocal { config, .. } | HotkeyMode::Disabled(config) => {
+                *config = new_config;
+                Ok(())
+            }
+        }
+    }
+
+    /// The hotkeys the main window should handle itself, if the global hotkey system isn't doing it for us.
+    pub const fn window_local_hotkeys(&self) -> Option<&HotkeyConfig> {
+        match &self.hotkeys {
+            HotkeyMode::WindowLocal {
+                config,
+                active: true,
+                ..
+            } => Some(config),
+            _ => None,
+        }
+    }
+
+    /// Whether a reset was asked for through a [`ResetRequest`] since this was last asked.
+    pub fn take_reset_request(&self) -> bool {
+        self.reset_request.take()
+    }
+
+    /// A handle for asking for resets from outside the app, see [`ResetRequest`].
+    pub fn reset_request(&self) -> ResetRequest {
+        self.reset_request.clone()
     }
 
     pub fn disable_hotkeys(&mut self) -> std::result::Result<(), livesplit_core::hotkey::Error> {
-        self.hks.deactivate()
+        match &mut self.hotkeys {
+            HotkeyMode::Global(hks, reset) => {
+                hks.deactivate()?;
+                reset.deactivate()
+            }
+            HotkeyMode::WindowLocal { active, .. } => {
+                *active = false;
+                Ok(())
+            }
+            HotkeyMode::Disabled(_) => Ok(()),
+        }
     }
 
     pub fn enable_hotkeys(&mut self) -> std::result::Result<(), livesplit_core::hotkey::Error> {
-        self.hks.activate()
+        match &mut self.hotkeys {
+            HotkeyMode::Global(hks, reset) => {
+                hks.activate()?;
+                reset.activate()
+            }
+            HotkeyMode::WindowLocal { active, .. } => {
+                *active = true;
+                Ok(())
+            }
+            HotkeyMode::Disabled(_) => Ok(()),
+        }
+    }
+}
+
+/// Pauses or resumes game time, starting it first if nothing has yet. The app only starts it by itself when comparing
+/// against game time.
+pub fn set_game_time_paused(timer: &mut Timer, paused: bool) {
+    if !timer.is_game_time_initialized() {
+        timer.initialize_game_time();
+    }
+
+    if paused {
+        timer.pause_game_time();
+    } else {
+        timer.resume_game_time();
     }
 }
 
+/// Reads a splits file in any of the formats livesplit-core can import.
+pub fn parse_splits(path: &Path) -> Result<ParsedRun<'static>, LoadSplitsError> {
+    let run_bytes = fs::read(path)?;
+    let run = composite::parse_and_fix(&run_bytes, path.parent())
+        .map_err(|e| diagnose_splits(&run_bytes, path, e))?;
+
+    Ok(run.into_owned())
+}
+
+/// The composite parser only reports that none of its parsers could read the file. Guess which format the file was
+/// meant to be in and run that parser on its own to find out what's wrong with it.
+fn diagnose_splits(run_bytes: &[u8], path: &Path, error: composite::Error) -> LoadSplitsError {
+    let Ok(source) = str::from_utf8(run_bytes) else {
+        // Llanfair is the only binary format
+        return parse_error(TimerKind::Llanfair, llanfair::parse(run_bytes), error);
+    };
+
+    let extension = path
+        .extension()
+        .and_then(OsStr::to_str)
+        .map(str::to_ascii_lowercase);
+    let Some(format) = guess_format(source, extension.as_deref()) else {
+        return LoadSplitsError::UnknownFormat;
+    };
+
+    // called the same way the composite parser calls them, so they fail the same way
+    let load_files_path = path.parent();
+    let load_icons = load_files_path.is_some();
+    match format {
+        TimerKind::LiveSplit => parse_error(format, livesplit::parse(source), error),
+        TimerKind::WSplit => parse_error(format, wsplit::parse(source, load_icons), error),
+        TimerKind::SplitterZ => parse_error(format, splitterz::parse(source, load_icons), error),
+        TimerKind::ShitSplit => parse_error(format, shit_split::parse(source), error),
+        TimerKind::Splitty => parse_error(format, splitty::parse(source), error),
+        TimerKind::TimeSplitTracker => parse_error(
+            format,
+            time_split_tracker::parse(source, load_files_path),
+            error,
+        ),
+        TimerKind::Portal2LiveTimer => {
+            parse_error(format, portal2_live_timer::parse(source), error)
+        }
+        TimerKind::FaceSplit => parse_error(format, face_split::parse(source, load_icons), error),
+        TimerKind::Flitter => parse_error(format, flitter::parse(source), error),
+        TimerKind::LlanfairGered => parse_error(format, llanfair_gered::parse(source), error),
+        TimerKind::Urn => parse_error(format, urn::parse(source), error),
+        TimerKind::SourceLiveTimer => parse_error(format, source_live_timer::parse(source), error),
+        TimerKind::Splitterino => parse_error(format, splitterino::parse(source), error),
+        TimerKind::SpeedRunIGT => parse_error(format, speedrun_igt::parse(source), error),
+        TimerKind::Generic(_) => parse_error(format, splits_io::parse(source), error),
+        TimerKind::Llanfair => parse_error(format, llanfair::parse(run_bytes), error),
+    }
+}
+
+/// Guesses the format of a text splits file from its extension, or failing that from what its first lines look like.
+fn guess_format(source: &str, extension: Option<&str>) -> Option<TimerKind<'static>> {
+    match extension {
+        Some("lss") => return Some(TimerKind::LiveSplit),
+        Some("wsplit") => return Some(TimerKind::WSplit),
+        Some("splits") => return Some(TimerKind::Splitterino),
+        Some("scm") => return Some(TimerKind::Flitter),
+        _ => {}
+    }
+
+    let source = source.trim_start_matches('\u{feff}').trim_start();
+    let mut lines = source.lines();
+    let first_line = lines.next()?;
+    let second_line = lines.next().unwrap_or_default();
+    let third_line = lines.next().unwrap_or_default();
+
+    let format = match source.chars().next()? {
+        // Gered's Llanfair wraps its run in a second Run element
+        '<' if source.matches("<Run>").count() + source.matches("<Run ").count() > 1 => {
+            TimerKind::LlanfairGered
+        }
+        '<' => TimerKind::LiveSplit,
+        '{' => guess_json_format(source),
+        '(' => TimerKind::Flitter,
+        '#' => TimerKind::ShitSplit,
+        _ if first_line.starts_with("Title=") => TimerKind::WSplit,
+        _ if first_line.contains('\t') => TimerKind::TimeSplitTracker,
+        _ if second_line.starts_with("sp_a1_intro1") => TimerKind::Portal2LiveTimer,
+        _ if first_line
+            .split(',')
+            .nth(1)
+            .is_some_and(|attempts| attempts.parse::<u32>().is_ok()) =>
+        {
+            TimerKind::SplitterZ
+        }
+        _ if third_line.parse::<u32>().is_ok() => TimerKind::FaceSplit,
+        _ => return None,
+    };
+
+    Some(format)
+}
+
+/// Most timers store their splits as JSON, tell them apart by the fields they use.
+fn guess_json_format(source: &str) -> TimerKind<'static> {
+    let Ok(serde_json::Value::Object(fields)) = serde_json::from_str(source) else {
+        // Urn's error is as good as any for JSON that doesn't parse at all
+        return TimerKind::Urn;
+    };
+
+    if fields.contains_key("_schemaVersion") {
+        TimerKind::Generic("splits.io".into())
+    } else if fields
+        .get("splits")
+        .is_some_and(serde_json::Value::is_object)
+    {
+        TimerKind::Splitterino
+    } else if fields.contains_key("Category") {
+        TimerKind::SourceLiveTimer
+    } else if fields.contains_key("speedrunigt_version") || fields.contains_key("mc_version") {
+        TimerKind::SpeedRunIGT
+    } else if fields.contains_key("run_name") || fields.contains_key("timer_type") {
+        TimerKind::Splitty
+    } else {
+        TimerKind::Urn
+    }
+}
+
+fn parse_error<T, E: std::error::Error>(
+    format: TimerKind<'static>,
+    result: Result<T, E>,
+    composite_error: composite::Error,
+) -> LoadSplitsError {
+    let Err(e) = result else {
+        // the composite parser gave up on a file this parser can read on its own, so its error is all there is
+        return LoadSplitsError::ParseError {
+            format,
+            reason: composite_error.to_string(),
+        };
+    };
+
+    // the parsers' errors only say which step failed, the details are further down the chain
+    let mut reason = e.to_string();
+    let mut source = e.source();
+    while let Some(e) = source {
+        reason = format!("{}: {e}", reason.trim_end_matches('.'));
+        source = e.source();
+    }
+
+    LoadSplitsError::ParseError { format, reason }
+}
+
+/// Reads a layout file, either one saved by us or an original LiveSplit .lsl layout.
+pub fn parse_layout(path: &Path) -> Result<Layout, LoadLayoutError> {
+    let mut reader = BufReader::new(File::open(path)?);
+
+    if let Ok(settings) = LayoutSettings::from_json(&mut reader) {
+        return Ok(Layout::from_settings(settings));
+    }
+
+    reader.seek(SeekFrom::Start(0))?;
+    let mut buf = String::new();
+    reader.read_to_string(&mut buf)?;
+
+    layout::parser::parse(&buf).map_err(|_| LoadLayoutError::ParseError)
+}
+
+/// Writes the timer's run out as a LiveSplit splits file.
+pub fn splits_to_string(timer: &Timer) -> Result<String, fmt::Error> {
+    // this is stupid but sadly necessary because of bad API design
+    let mut s = String::new();
+
+    livesplit_core::run::saver::livesplit::save_timer(timer, &mut s)?;
+
+    Ok(s)
+}
+
 #[derive(Error, Debug)]
 pub enum LoadSplitsError {
     #[error("Failed to load run")]
     IoError(#[from] io::Error),
-    #[error("Failed to parse run")]
-    ParseError,
+    #[error("Failed to parse run as {format} splits: {reason}")]
+    ParseError {
+        format: TimerKind<'static>,
+        reason: String,
+    },
+    #[error("Failed to parse run: the file isn't in any format LiveSplit can read")]
+    UnknownFormat,
+    #[error("The splits have no segments")]
+    NoSegments,
+    #[error("Can't load splits while the timer is running")]
+    TimerRunning,
 }
 
 #[derive(Error, Debug)]
@@ -203,6 +767,22 @@ pub enum SaveSplitsError {
     ParseError(#[from] fmt::Error),
 }
 
+#[derive(Error, Debug)]
+pub enum EditRunError {
+    #[error("The run can't be changed until the timer is reset")]
+    TimerRunning,
+    #[error("The run has no segments left")]
+    EmptyRun,
+}
+
+#[derive(Error, Debug)]
+pub enum SaveLayoutError {
+    #[error("Failed to write layout")]
+    IoError(#[from] io::Error),
+    #[error("Failed to serialize layout")]
+    SerializationError(#[from] serde_json::Error),
+}
+
 #[derive(Error, Debug)]
 pub enum LoadLayoutError {
     #[error("Failed to load layout")]
@@ -1,32 +1,63 @@
-use std::{path::PathBuf, time::Duration};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, mem,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
-use app_settings::Settings;
-use hotkeys::iced_key_to_livesplit_hotkey;
+use app_settings::{AutoSplitterSettings, CompareAgainst, Settings};
+use auto_splitter::AutoSplitterStatus;
+use clap::Parser;
+use cli::Args;
+use hotkeys::{hotkey_to_message, iced_key_to_livesplit_hotkey};
 use iced::{Event, Size, Subscription, Task, Theme, event, keyboard, window};
 
-use livesplit_core::{HotkeyConfig, hotkey::Hotkey};
-use livesplit_state::LivesplitState;
+use livesplit_core::{
+    HotkeyConfig, Run, RunEditor, Time, TimerPhase, hotkey::Hotkey, run::parser::TimerKind,
+};
+use livesplit_state::{LivesplitState, set_game_time_paused};
 use rfd::{AsyncMessageDialog, MessageDialogResult};
-use state::splits_editor::{self, SplitsEditorState};
-use ui::{edit_splits_window, main_window, settings_window};
+use state::{
+    attempt_history::{self, AttemptHistoryState},
+    layout_editor::{self, LayoutEditorState},
+    splits_editor::{self, IconData, SplitsEditorState},
+    sum_of_best_cleaner::{self, SumOfBestCleanerState},
+};
+use ui::{
+    attempt_history_window, edit_splits_window, layout_editor_window,
+    main_window::{self, RecentMenu},
+    settings_window, sum_of_best_cleaner_window,
+};
+use websocket_server::WebSocketServer;
 
 mod app_settings;
+mod auto_splitter;
+mod backup;
+mod cli;
+mod headless;
 mod hotkeys;
 mod livesplit_state;
+mod server;
 mod state;
 mod ui;
+mod websocket_server;
 mod widgets;
 
 fn main() -> Result<(), iced::Error> {
-    println!("Hello, world!");
+    let mut args = Args::parse();
+
+    if let Some(command) = args.command.take() {
+        std::process::exit(headless::run(command));
+    }
 
     iced::daemon(App::title, App::update, App::view)
         .subscription(App::subscription)
         .theme(App::theme)
-        .run_with(App::new)
+        .run_with(move || App::new(args))
 }
 
 #[derive(Clone, Debug)]
+#[allow(clippy::enum_variant_names)]
 enum Message {
     // Empty
     None,
@@ -38,6 +69,22 @@ enum Message {
 
     TimerTick,
 
+    // Timer control
+    StartOrSplit,
+    Reset,
+    ResetTimer {
+        update_splits: bool,
+    },
+    UndoSplit,
+    SkipSplit,
+    TogglePause,
+    UndoAllPauses,
+    PreviousComparison,
+    NextComparison,
+    ToggleTimingMethod,
+    SetCompareAgainst(CompareAgainst),
+    ToggleGameTimePause,
+
     // Hotkeys
     KeyEvent(window::Id, keyboard::Event),
     HotkeyBoxChangedFocus(usize, bool),
@@ -49,17 +96,83 @@ enum Message {
     TrySaveSplits,
     SaveSplits(PathBuf),
     TryLoadSplits,
+    TryLoadRecentSplits(PathBuf),
     LoadSplits(PathBuf),
     TryLoadLayout,
+    TryLoadRecentLayout(PathBuf),
     LoadLayout(PathBuf),
+    TrySaveLayout,
+    TrySaveLayoutAs,
+    SaveLayout(PathBuf),
     CloseRequested(window::Id),
+    ShowRecentMenu(Option<RecentMenu>),
+
+    // Auto splitter
+    TryLoadAutoSplitter,
+    LoadAutoSplitter(PathBuf),
+    UnloadAutoSplitter,
+    SetAutoSplitterSetting(String, bool),
+
+    // Server
+    ToggleServer(bool),
+    UpdateServerPort(String),
+    ServerPortBlur,
+    SetServerAllowLan(bool),
+    ToggleWebSocketServer(bool),
+    UpdateWebSocketPort(String),
+    WebSocketPortBlur,
+    SetWebSocketAllowLan(bool),
+    UpdateWebSocketOrigins(String),
+    WebSocketOriginsBlur,
+
+    // Auto saving
+    SetAutoSaveOnReset(bool),
+    SetAutoSaveOnPersonalBest(bool),
+
+    // Backups
+    TryChooseBackupDirectory,
+    SetBackupDirectory(Option<PathBuf>),
+    UpdateMaxBackups(String),
+    MaxBackupsBlur,
+    TryRestoreBackup,
+    RestoreSplits {
+        backup: PathBuf,
+        splits_path: Option<PathBuf>,
+    },
 
     // Splits Editing
     OpenEditSplitsWindow,
     SplitsEditorMessage(splits_editor::Message),
+    TryChooseGameIcon,
+    TryChooseSegmentIcon(usize),
+    ConfirmSplitsEdit,
+    CancelSplitsEdit,
+
+    // Sum of Best cleaning
+    OpenSumOfBestCleanerWindow,
+    SumOfBestCleanerMessage(sum_of_best_cleaner::Message),
+    ApplySumOfBestCleaner,
+    CancelSumOfBestCleaner,
+
+    // Attempt history
+    OpenAttemptHistoryWindow,
+    AttemptHistoryMessage(attempt_history::Message),
+    TryDeleteAttempts,
+    DeleteAttempts(BTreeSet<i32>),
+    TryClearAttemptHistory,
+    ClearAttemptHistory,
+
+    // Layout Editing
+    OpenLayoutEditorWindow,
+    LayoutEditorMessage(layout_editor::Message),
+    ApplyLayout,
+    CancelLayoutEdits,
 
     // Error
-    ErrorOccurred { title: String, error: String },
+    ErrorOccurred {
+        title: String,
+        error: String,
+    },
 }
 
 pub struct HotkeyBox {
@@ -74,7 +187,7 @@ impl HotkeyBox {
 }
 
 pub const fn load_hotkeys_from_hks(livesplit_state: &LivesplitState, hotkeys: &mut [HotkeyBox; 9]) {
-    let config = livesplit_state.hks.config();
+    let config = livesplit_state.hotkey_config();
 
     hotkeys[0].value = config.split;
     hotkeys[1].value = config.reset;
@@ -91,7 +204,7 @@ pub fn save_hotkeys_to_hks(
     livesplit_state: &mut LivesplitState,
     hotkeys: &[HotkeyBox; 9],
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let mut config = livesplit_state.hks.config();
+    let mut config = livesplit_state.hotkey_config();
 
     config.split = hotkeys[0].value;
     config.reset = hotkeys[1].value;
@@ -105,7 +218,7 @@ pub fn save_hotkeys_to_hks(
 
     // first clear the config so that we don't get false duplicate errors
 
-    livesplit_state.hks.set_config(HotkeyConfig {
+    livesplit_state.set_hotkey_config(HotkeyConfig {
         split: None,
         reset: None,
         undo: None,
@@ -117,7 +230,7 @@ pub fn save_hotkeys_to_hks(
         toggle_timing_method: None,
     })?;
 
-    livesplit_state.hks.set_config(config)?;
+    livesplit_state.set_hotkey_config(config)?;
     Ok(())
 }
 
@@ -125,8 +238,12 @@ struct App {
     main_window: window::Id,
     settings_window: Option<window::Id>,
     edit_splits_window: Option<window::Id>,
+    layout_editor_window: Option<window::Id>,
+    attempt_history_window: Option<window::Id>,
+    sum_of_best_cleaner_window: Option<window::Id>,
 
     settings: Settings,
+    settings_path: Option<PathBuf>,
 
     main_window_width: u32,
     main_window_height: u32,
@@ -134,51 +251,158 @@ struct App {
     livesplit_state: LivesplitState,
 
     splits_editor_state: Option<SplitsEditorState>,
+    layout_editor_state: Option<LayoutEditorState>,
+    attempt_history_state: Option<AttemptHistoryState>,
+    sum_of_best_cleaner_state: Option<SumOfBestCleanerState>,
 
     hotkeys: [HotkeyBox; 9],
     hotkey_focused: Option<usize>,
+
+    server_port_buffer: String,
+
+    websocket_server: Option<WebSocketServer>,
+    websocket_port_buffer: String,
+    websocket_origins_buffer: String,
+
+    recent_menu: Option<RecentMenu>,
+
+    max_backups_buffer: String,
+    /// The timer phase as of the last tick, to notice resets no matter where they came from.
+    last_phase: TimerPhase,
+    /// The personal best as of the last tick, to notice when a reset records a new one.
+    last_personal_best: Time,
+    /// The file and format of splits read from another timer, which saving must never write LiveSplit splits over.
+    foreign_splits: Option<(PathBuf, TimerKind<'static>)>,
 }
 enum WindowType {
     Main,
     Settings,
     EditSplits,
+    LayoutEditor,
+    AttemptHistory,
+    SumOfBestCleaner,
     Untracked,
 }
 impl App {
-    pub fn new() -> (Self, Task<Message>) {
+    pub fn new(args: Args) -> (Self, Task<Message>) {
         let (main_window, window_open_task) = window::open(window::Settings {
             exit_on_close_request: false,
             ..Default::default()
         });
 
-        let settings = Settings::load().unwrap_or_default();
+        let settings_path = args.settings.or_else(Settings::default_path);
+        let mut settings = match &settings_path {
+            Some(path) if !args.reset_settings => Settings::load(path).unwrap_or_default(),
+            _ => Settings::default(),
+        };
+
+        // settings saved before there were recent files lists only know about the last files opened
+        if settings.recent_splits.is_empty()
+            && let Some(path) = settings.splits_path.clone()
+        {
+            settings.add_recent_splits(path);
+        }
+        if settings.recent_layouts.is_empty()
+            && let Some(path) = settings.layout_path.clone()
+        {
+            settings.add_recent_layout(path);
+        }
+        let livesplit_state = LivesplitState::with_settings(&settings, !args.no_hotkeys);
+        let last_personal_best = livesplit_state.personal_best_time();
+
+        let recovery_task = match backup::recovery(&settings.backup) {
+            Some(recovery) => Task::future(async move {
+                let restore = AsyncMessageDialog::new()
+                    .set_buttons(rfd::MessageButtons::YesNo)
+                    .set_title("Restore splits?")
+                    .set_description(
+                        "LiveSplit didn't shut down properly last time. Would you like to restore the splits you had open, including any attempts that weren't saved?",
+                    )
+                    .show()
+                    .await;
+
+                match restore {
+                    MessageDialogResult::Yes => Message::RestoreSplits {
+                        backup: recovery.file,
+                        splits_path: recovery.splits_path,
+                    },
+                    _ => Message::None,
+                }
+            }),
+            None => Task::none(),
+        };
+
+        let (websocket_server, websocket_task) = if settings.websocket.enabled {
+            let (server, task) = WebSocketServer::start(
+                &settings.websocket,
+                livesplit_state.timer.clone(),
+                livesplit_state.reset_request(),
+            );
+            (Some(server), task)
+        } else {
+            (None, Task::none())
+        };
+
+        let mut app = Self {
+            main_window,
+            settings_window: None,
+            edit_splits_window: None,
+            layout_editor_window: None,
+            attempt_history_window: None,
+            sum_of_best_cleaner_window: None,
+            main_window_width: 0,
+            main_window_height: 0,
+            livesplit_state,
+            splits_editor_state: None,
+            layout_editor_state: None,
+            attempt_history_state: None,
+            sum_of_best_cleaner_state: None,
+
+            hotkeys: [
+                "Start/Split",
+                "Reset",
+                "Undo",
+                "Skip",
+                "Pause",
+                "Undo All Pauses",
+                "Previous Comparison",
+                "Next Comparison",
+                "Toggle Timing Method",
+            ]
+            .map(HotkeyBox::new),
+            hotkey_focused: None,
+
+            server_port_buffer: settings.server.port.to_string(),
+
+            websocket_server,
+            websocket_port_buffer: settings.websocket.port.to_string(),
+            websocket_origins_buffer: settings.websocket.allowed_origins.join(", "),
+            recent_menu: None,
+            max_backups_buffer: settings.backup.max_backups.to_string(),
+            last_phase: TimerPhase::NotRunning,
+            last_personal_best,
+            foreign_splits: None,
+            settings,
+            settings_path,
+        };
+
+        // loaded after the settings' files, so anything that goes wrong gets reported
+        let splits_task = args
+            .splits
+            .map_or_else(Task::none, |path| app.update(Message::LoadSplits(path)));
+        let layout_task = args
+            .layout
+            .map_or_else(Task::none, |path| app.update(Message::LoadLayout(path)));
 
         (
-            Self {
-                main_window,
-                settings_window: None,
-                edit_splits_window: None,
-                main_window_width: 0,
-                main_window_height: 0,
-                livesplit_state: LivesplitState::with_settings(&settings),
-                splits_editor_state: None,
-                settings,
-
-                hotkeys: [
-                    "Start/Split",
-                    "Reset",
-                    "Undo",
-                    "Skip",
-                    "Pause",
-                    "Undo All Pauses",
-                    "Previous Comparison",
-                    "Next Comparison",
-                    "Toggle Timing Method",
-                ]
-                .map(HotkeyBox::new),
-                hotkey_focused: None,
-            },
-            window_open_task.discard(),
+            app,
+            Task::batch([
+                window_open_task.discard(),
+                websocket_task,
+                recovery_task,
+                splits_task,
+                layout_task,
+            ]),
         )
     }
     pub fn view(&self, window: window::Id) -> iced::Element<'_, Message, Theme> {
@@ -186,6 +410,9 @@ impl App {
             WindowType::Main => main_window::view(self),
             WindowType::Settings => settings_window::view(self),
             WindowType::EditSplits => edit_splits_window::view(self),
+            WindowType::LayoutEditor => layout_editor_window::view(self),
+            WindowType::AttemptHistory => attempt_history_window::view(self),
+            WindowType::SumOfBestCleaner => sum_of_best_cleaner_window::view(self),
             WindowType::Untracked => panic!("Tried to view untracked window"),
         }
     }
@@ -193,9 +420,105 @@ impl App {
     pub fn update(&mut self, message: Message) -> Task<Message> {
         match message {
             Message::None => {}
-            Message::TimerTick => self
+            Message::TimerTick => {
+                if self.livesplit_state.take_reset_request() {
+                    return Task::done(Message::Reset);
+                }
+
+                self.livesplit_state
+                    .update(self.main_window_width, self.main_window_height);
+
+                // pick up attempts finished or reset while the window is open
+                if let Some(attempt_history_state) = self.attempt_history_state.as_mut() {
+                    let timer = self
+                        .livesplit_state
+                        .timer
+                        .read()
+                        .expect("Timer lock poisoned!");
+                    if timer.run().attempt_history().len() != attempt_history_state.attempts.len() {
+                        attempt_history_state.refresh(timer.run());
+                    }
+                }
+
+                let phase = self.livesplit_state.current_phase();
+                let last_phase = mem::replace(&mut self.last_phase, phase);
+                let personal_best = self.livesplit_state.personal_best_time();
+                let last_personal_best = mem::replace(&mut self.last_personal_best, personal_best);
+
+                // resets can come from hotkeys, the servers and the auto splitter, so watch for them here
+                if phase == TimerPhase::NotRunning && last_phase != TimerPhase::NotRunning {
+                    let auto_save = self.settings.auto_save;
+                    // the personal best only gets written into the run when the finished attempt is reset
+                    let new_personal_best = personal_best != last_personal_best;
+
+                    if let Some(path) = self.settings.splits_path.clone()
+                        && (auto_save.on_reset || auto_save.on_personal_best && new_personal_best)
+                    {
+                        return Task::done(Message::SaveSplits(path));
+                    }
+
+                    if let Err(e) = self
+                        .livesplit_state
+                        .save_recovery(&self.settings.backup, self.settings.splits_path.as_deref())
+                    {
+                        return Task::done(Message::ErrorOccurred {
+                            title: "Failed to write recovery file".to_owned(),
+                            error: e.to_string(),
+                        });
+                    }
+                }
+            }
+            Message::StartOrSplit => self.livesplit_state.timer_mut().split_or_start(),
+            Message::Reset => {
+                if !self.livesplit_state.reset_has_new_bests() {
+                    self.livesplit_state.timer_mut().reset(true);
+                    return Task::none();
+                }
+
+                return Task::future(async {
+                    match AsyncMessageDialog::new()
+                        .set_buttons(rfd::MessageButtons::YesNoCancel)
+                        .set_title("Update splits?")
+                        .set_description(
+                            "This run has beaten some of your best times. Would you like to update your splits?",
+                        )
+                        .show()
+                        .await
+                    {
+                        MessageDialogResult::Yes => Message::ResetTimer {
+                            update_splits: true,
+                        },
+                        MessageDialogResult::No => Message::ResetTimer {
+                            update_splits: false,
+                        },
+                        _ => Message::None,
+                    }
+                });
+            }
+            Message::ResetTimer { update_splits } => {
+                self.livesplit_state.timer_mut().reset(update_splits);
+            }
+            Message::UndoSplit => self.livesplit_state.timer_mut().undo_split(),
+            Message::SkipSplit => self.livesplit_state.timer_mut().skip_split(),
+            Message::TogglePause => self.livesplit_state.timer_mut().toggle_pause_or_start(),
+            Message::UndoAllPauses => self.livesplit_state.timer_mut().undo_all_pauses(),
+            Message::PreviousComparison => self
                 .livesplit_state
-                .update(self.main_window_width, self.main_window_height),
+                .timer_mut()
+                .switch_to_previous_comparison(),
+            Message::NextComparison => self.livesplit_state.timer_mut().switch_to_next_comparison(),
+            Message::ToggleTimingMethod => self.livesplit_state.timer_mut().toggle_timing_method(),
+            Message::SetCompareAgainst(compare_against) => {
+                self.settings.compare_against = compare_against;
+                self.livesplit_state
+                    .timer_mut()
+                    .set_current_timing_method(compare_against.into());
+            }
+            Message::ToggleGameTimePause => {
+                let mut timer = self.livesplit_state.timer_mut();
+                let paused = timer.is_game_time_paused();
+                set_game_time_paused(&mut timer, !paused);
+            }
             Message::WindowResized(id, size) => {
                 if id == self.main_window {
                     self.main_window_width = size.width as u32;
@@ -214,6 +537,47 @@ impl App {
                 self.splits_editor_state = Some(SplitsEditorState::new(&mut self.livesplit_state));
                 return window_task.discard();
             }
+            Message::OpenLayoutEditorWindow => {
+                if self.layout_editor_window.is_some() {
+                    return Task::none();
+                }
+
+                let layout_editor_state = match LayoutEditorState::new(&self.livesplit_state) {
+                    Ok(state) => state,
+                    Err(e) => {
+                        return Task::done(Message::ErrorOccurred {
+                            title: "Failed to open layout editor".to_owned(),
+                            error: e.to_string(),
+                        });
+                    }
+                };
+
+                self.livesplit_state.disable_hotkeys().ok();
+
+                let (id, window_task) = window::open(window::Settings::default());
+
+                self.layout_editor_window = Some(id);
+                self.layout_editor_state = Some(layout_editor_state);
+
+                return window_task.discard();
+            }
+            Message::OpenAttemptHistoryWindow => {
+                if self.attempt_history_window.is_some() {
+                    return Task::none();
+                }
+
+                let (id, window_task) = window::open(window::Settings::default());
+
+                self.attempt_history_window = Some(id);
+                self.attempt_history_state = Some(AttemptHistoryState::new(
+                    self.livesplit_state
+                        .timer
+                        .read()
+                        .expect("Timer lock poisoned!")
+                        .run(),
+                ));
+                return window_task.discard();
+            }
             Message::OpenSettingsWindow => {
                 self.livesplit_state.disable_hotkeys().ok();
 
@@ -239,9 +603,22 @@ impl App {
             }
             Message::WindowClosed(window) => match self.identify_window(window) {
                 WindowType::Main => {
+                    // the toggle timing method hotkey can switch this without going through us
+                    self.settings.compare_against =
+                        self.livesplit_state.current_timing_method().into();
+
                     // the window is already closed - we can't do anything about this
-                    self.settings.save().ok();
-                    return iced::exit();
+                    self.settings.save(self.settings_path.as_deref()).ok();
+
+                    // the user has already chosen whether to save, so there's nothing left to recover
+                    backup::clear_recovery(&self.settings.backup).ok();
+
+                    // let overlays know we're going away before the runtime stops
+                    let shutdown_task = self
+                        .websocket_server
+                        .take()
+                        .map_or_else(Task::none, WebSocketServer::shutdown);
+                    return shutdown_task.chain(iced::exit());
                 }
                 WindowType::Settings => {
                     self.settings_window = None;
@@ -257,9 +634,42 @@ impl App {
                 WindowType::EditSplits => {
                     self.edit_splits_window = None;
 
-                    if let Some(splits_editor_state) = self.splits_editor_state.take() {
-                        splits_editor_state.close_window(&mut self.livesplit_state);
-                    }
+                    // only OK applies the edits, closing the window any other way cancels them
+                    self.splits_editor_state = None;
+
+                    // the cleaner works on the editor's run, so it can't outlive it
+                    let close_cleaner_task = self
+                        .sum_of_best_cleaner_window
+                        .map_or_else(Task::none, window::close);
+
+                    if let Err(e) = self.livesplit_state.enable_hotkeys() {
+                        return close_cleaner_task.chain(Task::done(Message::ErrorOccurred {
+                            title: "Failed to re-enable hotkeys".to_owned(),
+                            error: e.to_string(),
+                        }));
+                    };
+
+                    return close_cleaner_task;
+                }
+                WindowType::LayoutEditor => {
+                    // like the splits editor, closing without applying throws the edits away
+                    self.layout_editor_window = None;
+                    self.layout_editor_state = None;
+
+                    if let Err(e) = self.livesplit_state.enable_hotkeys() {
+                        return Task::done(Message::ErrorOccurred {
+                            title: "Failed to re-enable hotkeys".to_owned(),
+                            error: e.to_string(),
+                        });
+                    };
+                }
+                WindowType::AttemptHistory => {
+                    self.attempt_history_window = None;
+                    self.attempt_history_state = None;
+                }
+                WindowType::SumOfBestCleaner => {
+                    self.sum_of_best_cleaner_window = None;
+                    self.sum_of_best_cleaner_state = None;
                 }
                 WindowType::Untracked => panic!("Tried to close untracked window"),
             },
@@ -269,13 +679,34 @@ impl App {
                     modifiers,
                     ..
                 } = evt
+                    && self.settings_window == Some(id)
                 {
-                    if self.settings_window == Some(id) {
-                        self.hotkey_focused.inspect(|f| {
-                            self.hotkeys[*f].value =
-                                iced_key_to_livesplit_hotkey(physical_key, modifiers);
-                        });
-                    }
+                    self.hotkey_focused.inspect(|f| {
+                        self.hotkeys[*f].value =
+                            iced_key_to_livesplit_hotkey(physical_key, modifiers);
+                    });
+                } else if let keyboard::Event::KeyPressed {
+                    physical_key,
+                    modifiers,
+                    ..
+                } = evt
+                    && id == self.main_window
+                    && let Some(config) = self.livesplit_state.window_local_hotkeys()
+                    && let Some(hotkey) = iced_key_to_livesplit_hotkey(physical_key, modifiers)
+                    && let Some(message) = hotkey_to_message(config, hotkey)
+                {
+                    return Task::done(message);
+                } else if let keyboard::Event::KeyPressed { key, modifiers, .. } = evt
+                    && self.edit_splits_window == Some(id)
+                    && modifiers.command()
+                    && let keyboard::Key::Character(c) = key
+                {
+                    let message = match (c.to_lowercase().as_str(), modifiers.shift()) {
+                        ("z", false) => splits_editor::Message::Undo,
+                        ("y", _) | ("z", true) => splits_editor::Message::Redo,
+                        _ => return Task::none(),
+                    };
+                    return Task::done(message.into_app_message());
                 }
             }
             Message::HotkeyBoxChangedFocus(id, focus) => {
@@ -294,21 +725,62 @@ impl App {
                 }
                 let (load_task, lth) = Task::future(Self::get_load_splits_path()).abortable();
 
-                let save_if_dirty_task = self.save_if_dirty(lth);
+                // the layout only gets asked about if the new splits bring their own
+                let save_if_dirty_task = self.save_splits_if_dirty(lth);
+
+                return save_if_dirty_task.chain(load_task);
+            }
+            Message::TryLoadRecentSplits(path) => {
+                self.recent_menu = None;
+                if self.livesplit_state.is_timer_mid_run() {
+                    return Task::none();
+                }
+                let (load_task, lth) = Task::done(Message::LoadSplits(path)).abortable();
+
+                let save_if_dirty_task = self.save_splits_if_dirty(lth);
 
                 return save_if_dirty_task.chain(load_task);
             }
             Message::LoadSplits(path) => {
-                if let Err(e) = self.livesplit_state.load_splits(&path) {
-                    return Task::done(Message::ErrorOccurred {
-                        title: "Failed to load splits".to_owned(),
-                        error: e.to_string(),
-                    });
+                let kind = match self.livesplit_state.load_splits(&path) {
+                    Ok(kind) => kind,
+                    Err(e) => {
+                        return Task::done(Message::ErrorOccurred {
+                            title: "Failed to load splits".to_owned(),
+                            error: e.to_string(),
+                        });
+                    }
+                };
+                self.settings.add_recent_splits(path.clone());
+
+                // we only write LiveSplit splits, so don't let saving go anywhere near another timer's file
+                if kind != TimerKind::LiveSplit {
+                    self.settings.splits_path = None;
+                    self.foreign_splits = Some((path.clone(), kind.clone()));
+                    return self
+                        .switch_auto_splitter()
+                        .chain(Task::future(Self::offer_save_as_livesplit(path, kind)));
                 }
                 self.settings.splits_path.replace(path);
+                self.foreign_splits = None;
+
+                // bring back the layout these splits were last used with. Loaded right away unless there are
+                // layout edits to ask about, so a layout picked straight after the splits (e.g. on the command
+                // line) wins.
+                let load_layout_task = match self.settings.linked_layout() {
+                    Some(layout) if self.settings.layout_path.as_ref() != Some(layout) => {
+                        self.update(Message::TryLoadRecentLayout(layout.clone()))
+                    }
+                    _ => Task::none(),
+                };
+
+                return load_layout_task.chain(self.switch_auto_splitter());
             }
             Message::TrySaveSplits => {
-                return Task::future(Self::get_save_splits_path(None));
+                return Task::future(Self::get_save_splits_path(
+                    None,
+                    self.foreign_splits.clone(),
+                ));
             }
             Message::SaveSplits(path) => {
                 println!("Saving splits!");
@@ -319,9 +791,26 @@ impl App {
                         error: e.to_string(),
                     });
                 }
+
+                // splits that weren't loaded from a LiveSplit file get this one from now on
+                if self.settings.splits_path.is_none() {
+                    self.settings.add_recent_splits(path.clone());
+                    self.settings.splits_path = Some(path.clone());
+                    self.foreign_splits = None;
+                }
+
+                // everything in the recovery file is in the real splits now
+                let result = backup::back_up_splits(&self.settings.backup, &path)
+                    .and_then(|()| backup::clear_recovery(&self.settings.backup));
+                if let Err(e) = result {
+                    return Task::done(Message::ErrorOccurred {
+                        title: "Failed to back up splits".to_owned(),
+                        error: e.to_string(),
+                    });
+                }
             }
             Message::TryLoadLayout => {
-                return Task::future(async {
+                let (load_task, lth) = Task::future(async {
                     match rfd::AsyncFileDialog::new()
                         //.add_filter("LiveSplit Splits Files", &["*.lss"])
                         .set_title("Load Layout")
@@ -331,17 +820,248 @@ impl App {
                         Some(path) => Message::LoadLayout(path.path().to_owned()),
                         None => Message::None,
                     }
-                });
+                })
+                .abortable();
+
+                let save_if_dirty_task = self.save_layout_if_dirty(lth);
+
+                return save_if_dirty_task.chain(load_task);
+            }
+            Message::TryLoadRecentLayout(path) => {
+                self.recent_menu = None;
+                if !self.livesplit_state.is_layout_dirty() {
+                    return self.update(Message::LoadLayout(path));
+                }
+                let (load_task, lth) = Task::done(Message::LoadLayout(path)).abortable();
+
+                let save_if_dirty_task = self.save_layout_if_dirty(lth);
+
+                return save_if_dirty_task.chain(load_task);
             }
             Message::LoadLayout(path) => {
+                self.recent_menu = None;
                 if let Err(e) = self.livesplit_state.load_layout(&path) {
                     return Task::done(Message::ErrorOccurred {
                         title: "Failed to load layout".to_owned(),
                         error: e.to_string(),
                     });
                 }
+                self.settings.add_recent_layout(path.clone());
                 self.settings.layout_path.replace(path);
             }
+            Message::TrySaveLayout => {
+                if let Some(path) = self.settings.layout_path.clone() {
+                    return Task::done(Message::SaveLayout(path));
+                }
+                return Task::future(Self::get_save_layout_path(None));
+            }
+            Message::TrySaveLayoutAs => {
+                return Task::future(Self::get_save_layout_path(None));
+            }
+            Message::SaveLayout(path) => {
+                if let Err(e) = self.livesplit_state.save_layout(&path) {
+                    return Task::done(Message::ErrorOccurred {
+                        title: "Failed to save layout".to_owned(),
+                        error: e.to_string(),
+                    });
+                }
+                self.settings.add_recent_layout(path.clone());
+                self.settings.layout_path.replace(path);
+            }
+            Message::ShowRecentMenu(recent_menu) => self.recent_menu = recent_menu,
+            Message::SetAutoSaveOnReset(enabled) => self.settings.auto_save.on_reset = enabled,
+            Message::SetAutoSaveOnPersonalBest(enabled) => {
+                self.settings.auto_save.on_personal_best = enabled;
+            }
+            Message::TryChooseBackupDirectory => {
+                return Task::future(async {
+                    match rfd::AsyncFileDialog::new()
+                        .set_title("Choose Backup Directory")
+                        .pick_folder()
+                        .await
+                    {
+                        Some(path) => Message::SetBackupDirectory(Some(path.path().to_owned())),
+                        None => Message::None,
+                    }
+                });
+            }
+            Message::SetBackupDirectory(directory) => self.settings.backup.directory = directory,
+            Message::UpdateMaxBackups(text) => self.max_backups_buffer = text,
+            Message::MaxBackupsBlur => match self.max_backups_buffer.parse() {
+                Ok(max_backups) => self.settings.backup.max_backups = max_backups,
+                Err(_) => self.max_backups_buffer = self.settings.backup.max_backups.to_string(),
+            },
+            Message::TryRestoreBackup => {
+                if self.livesplit_state.is_timer_mid_run() {
+                    return Task::none();
+                }
+                let mut dialog = rfd::AsyncFileDialog::new()
+                    .add_filter("LiveSplit Splits Files", &["lss"])
+                    .set_title("Restore Backup");
+                // start in the open splits' backups if there are any
+                let directory = self
+                    .settings
+                    .splits_path
+                    .as_deref()
+                    .and_then(|path| backup::splits_backup_directory(&self.settings.backup, path))
+                    .filter(|directory| directory.is_dir())
+                    .or_else(|| self.settings.backup.directory());
+                if let Some(directory) = directory {
+                    dialog = dialog.set_directory(directory);
+                }
+
+                let (restore_task, rth) = Task::future(async {
+                    match dialog.pick_file().await {
+                        Some(path) => Message::RestoreSplits {
+                            backup: path.path().to_owned(),
+                            splits_path: None,
+                        },
+                        None => Message::None,
+                    }
+                })
+                .abortable();
+
+                let save_if_dirty_task = self.save_if_dirty(rth);
+
+                return save_if_dirty_task.chain(restore_task);
+            }
+            Message::RestoreSplits {
+                backup,
+                splits_path,
+            } => {
+                if let Err(e) = self.livesplit_state.restore_splits(&backup) {
+                    return Task::done(Message::ErrorOccurred {
+                        title: "Failed to restore splits".to_owned(),
+                        error: e.to_string(),
+                    });
+                }
+
+                // the recovery file knows which splits it came from, backups just go over the open splits
+                if let Some(path) = splits_path
+                    && self.settings.splits_path.as_ref() != Some(&path)
+                {
+                    self.settings.add_recent_splits(path.clone());
+                    self.settings.splits_path.replace(path);
+                    self.foreign_splits = None;
+                    return self.switch_auto_splitter();
+                }
+            }
+            Message::TryLoadAutoSplitter => {
+                return Task::future(async {
+                    match rfd::AsyncFileDialog::new()
+                        .add_filter("WebAssembly Auto Splitters", &["wasm"])
+                        .set_title("Load Auto Splitter")
+                        .pick_file()
+                        .await
+                    {
+                        Some(path) => Message::LoadAutoSplitter(path.path().to_owned()),
+                        None => Message::None,
+                    }
+                });
+            }
+            Message::LoadAutoSplitter(module_path) => {
+                let auto_splitter = AutoSplitterSettings {
+                    module_path,
+                    user_settings: BTreeMap::new(),
+                };
+
+                if let Err(e) = self
+                    .livesplit_state
+                    .auto_splitter
+                    .load(&auto_splitter.module_path, &auto_splitter.user_settings)
+                {
+                    return Task::done(Message::ErrorOccurred {
+                        title: "Failed to load auto splitter".to_owned(),
+                        error: e.to_string(),
+                    });
+                }
+
+                if let Some(splits_path) = self.settings.splits_path.clone() {
+                    self.settings
+                        .auto_splitters
+                        .insert(splits_path, auto_splitter);
+                }
+            }
+            Message::UnloadAutoSplitter => {
+                self.livesplit_state.auto_splitter.unload().ok();
+
+                if let Some(splits_path) = &self.settings.splits_path {
+                    self.settings.auto_splitters.remove(splits_path);
+                }
+            }
+            Message::SetAutoSplitterSetting(key, value) => {
+                let AutoSplitterStatus::Running(module_path) =
+                    self.livesplit_state.auto_splitter.status()
+                else {
+                    return Task::none();
+                };
+
+                let mut user_settings: BTreeMap<_, _> = self
+                    .livesplit_state
+                    .auto_splitter
+                    .user_settings()
+                    .into_iter()
+                    .map(|setting| (setting.key, setting.value))
+                    .collect();
+                user_settings.insert(key, value);
+
+                // scripts only read their settings while starting up, so restart it with the new values
+                if let Err(e) = self
+                    .livesplit_state
+                    .auto_splitter
+                    .load(&module_path, &user_settings)
+                {
+                    return Task::done(Message::ErrorOccurred {
+                        title: "Failed to reload auto splitter".to_owned(),
+                        error: e.to_string(),
+                    });
+                }
+
+                if let Some(auto_splitter) = self.settings.auto_splitter_mut() {
+                    auto_splitter.user_settings = user_settings;
+                }
+            }
+            Message::ToggleServer(enabled) => self.settings.server.enabled = enabled,
+            Message::UpdateServerPort(text) => self.server_port_buffer = text,
+            // only apply the port once editing is done, otherwise we'd rebind on every keystroke
+            Message::ServerPortBlur => match self.server_port_buffer.parse() {
+                Ok(port) => self.settings.server.port = port,
+                Err(_) => self.server_port_buffer = self.settings.server.port.to_string(),
+            },
+            Message::SetServerAllowLan(allow_lan) => self.settings.server.allow_lan = allow_lan,
+            Message::ToggleWebSocketServer(enabled) => {
+                self.settings.websocket.enabled = enabled;
+                return self.restart_websocket_server();
+            }
+            Message::UpdateWebSocketPort(text) => self.websocket_port_buffer = text,
+            Message::WebSocketPortBlur => match self.websocket_port_buffer.parse() {
+                Ok(port) if port != self.settings.websocket.port => {
+                    self.settings.websocket.port = port;
+                    return self.restart_websocket_server();
+                }
+                Ok(_) => {}
+                Err(_) => self.websocket_port_buffer = self.settings.websocket.port.to_string(),
+            },
+            Message::SetWebSocketAllowLan(allow_lan) => {
+                self.settings.websocket.allow_lan = allow_lan;
+                return self.restart_websocket_server();
+            }
+            Message::UpdateWebSocketOrigins(text) => self.websocket_origins_buffer = text,
+            Message::WebSocketOriginsBlur => {
+                let origins: Vec<String> = self
+                    .websocket_origins_buffer
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|origin| !origin.is_empty())
+                    .map(str::to_owned)
+                    .collect();
+                self.websocket_origins_buffer = origins.join(", ");
+
+                if origins != self.settings.websocket.allowed_origins {
+                    self.settings.websocket.allowed_origins = origins;
+                    return self.restart_websocket_server();
+                }
+            }
             Message::CloseRequested(window) => {
                 if let WindowType::Main = self.identify_window(window) {
                     let (close_window_task, ct) = window::close::<Message>(window).abortable();
@@ -376,11 +1096,161 @@ impl App {
 
                 panic!("Tried to close untracked window")
             }
-            Message::SplitsEditorMessage(message) => self
-                .splits_editor_state
+            Message::SplitsEditorMessage(message) => {
+                // the icon pickers don't block the editor, so it may have been closed while one was open
+                if let Some(splits_editor_state) = self.splits_editor_state.as_mut() {
+                    splits_editor_state.update(message);
+                }
+            }
+            Message::TryChooseGameIcon => {
+                return Task::future(Self::pick_icon()).and_then(|icon| {
+                    Task::done(splits_editor::Message::SetGameIcon(icon).into_app_message())
+                });
+            }
+            Message::TryChooseSegmentIcon(idx) => {
+                return Task::future(Self::pick_icon()).and_then(move |icon| {
+                    Task::done(splits_editor::Message::SetSegmentIcon(idx, icon).into_app_message())
+                });
+            }
+            Message::ConfirmSplitsEdit | Message::CancelSplitsEdit => {
+                if let Some(splits_editor_state) = self.splits_editor_state.take()
+                    && let Message::ConfirmSplitsEdit = message
+                {
+                    splits_editor_state.apply(&mut self.livesplit_state);
+                }
+
+                if let Some(id) = self.edit_splits_window {
+                    return window::close(id);
+                }
+            }
+            Message::OpenSumOfBestCleanerWindow => {
+                if self.sum_of_best_cleaner_window.is_some() {
+                    return Task::none();
+                }
+                let Some(splits_editor_state) = self.splits_editor_state.as_mut() else {
+                    return Task::none();
+                };
+
+                let revision = splits_editor_state.revision();
+                let sum_of_best_cleaner_state =
+                    SumOfBestCleanerState::new(&mut splits_editor_state.editor, revision);
+
+                if sum_of_best_cleaner_state.entries.is_empty() {
+                    return Task::future(
+                        AsyncMessageDialog::new()
+                            .set_title("Clean Sum of Best")
+                            .set_description("There is nothing to clean up.")
+                            .show(),
+                    )
+                    .discard();
+                }
+
+                let (id, window_task) = window::open(window::Settings::default());
+
+                self.sum_of_best_cleaner_window = Some(id);
+                self.sum_of_best_cleaner_state = Some(sum_of_best_cleaner_state);
+                return window_task.discard();
+            }
+            Message::SumOfBestCleanerMessage(message) => {
+                if let Some(sum_of_best_cleaner_state) = self.sum_of_best_cleaner_state.as_mut() {
+                    sum_of_best_cleaner_state.update(message);
+                }
+            }
+            Message::ApplySumOfBestCleaner | Message::CancelSumOfBestCleaner => {
+                if let Some(sum_of_best_cleaner_state) = self.sum_of_best_cleaner_state.take()
+                    && let Message::ApplySumOfBestCleaner = message
+                    && let Some(splits_editor_state) = self.splits_editor_state.as_mut()
+                {
+                    if sum_of_best_cleaner_state.revision != splits_editor_state.revision() {
+                        let close_task = self
+                            .sum_of_best_cleaner_window
+                            .map_or_else(Task::none, window::close);
+                        return close_task.chain(Task::done(Message::ErrorOccurred {
+                            title: "Failed to clean sum of best".to_owned(),
+                            error: "The splits were edited while the cleaner was open. Open it again to see what's left to clean up."
+                                .to_owned(),
+                        }));
+                    }
+                    splits_editor_state.clean_sum_of_best(sum_of_best_cleaner_state);
+                }
+
+                if let Some(id) = self.sum_of_best_cleaner_window {
+                    return window::close(id);
+                }
+            }
+            Message::AttemptHistoryMessage(message) => {
+                if let Some(attempt_history_state) = self.attempt_history_state.as_mut() {
+                    attempt_history_state.update(message);
+                }
+            }
+            Message::TryDeleteAttempts => {
+                let Some(attempt_history_state) = &self.attempt_history_state else {
+                    return Task::none();
+                };
+                let selected = attempt_history_state.selected.clone();
+
+                return Task::future(async move {
+                    let confirmed = AsyncMessageDialog::new()
+                        .set_buttons(rfd::MessageButtons::YesNo)
+                        .set_title("Delete attempts?")
+                        .set_description(format!(
+                            "Delete {} attempts and the segment times recorded during them? This can't be undone.",
+                            selected.len()
+                        ))
+                        .show()
+                        .await;
+
+                    match confirmed {
+                        MessageDialogResult::Yes => Message::DeleteAttempts(selected),
+                        _ => Message::None,
+                    }
+                });
+            }
+            Message::DeleteAttempts(indices) => {
+                return self.edit_attempt_history(|run| {
+                    attempt_history::remove_attempts(run, &indices);
+                });
+            }
+            Message::TryClearAttemptHistory => {
+                return Task::future(async {
+                    let confirmed = AsyncMessageDialog::new()
+                        .set_buttons(rfd::MessageButtons::YesNo)
+                        .set_title("Clear history?")
+                        .set_description(
+                            "Delete every attempt and all segment history? This can't be undone.",
+                        )
+                        .show()
+                        .await;
+
+                    match confirmed {
+                        MessageDialogResult::Yes => Message::ClearAttemptHistory,
+                        _ => Message::None,
+                    }
+                });
+            }
+            Message::ClearAttemptHistory => {
+                return self.edit_attempt_history(|run| {
+                    let mut editor = RunEditor::new(run.clone())
+                        .expect("The timer never holds a run without segments");
+                    editor.clear_history();
+                    *run = editor.close();
+                });
+            }
+            Message::LayoutEditorMessage(message) => self
+                .layout_editor_state
                 .as_mut()
-                .expect("Recieved a splits editor messagge when the splits editor was closed")
+                .expect("Recieved a layout editor message when the layout editor was closed")
                 .update(message),
+            Message::ApplyLayout => {
+                if let Some(layout_editor_state) = self.layout_editor_state.as_mut() {
+                    layout_editor_state.apply(&mut self.livesplit_state);
+                }
+            }
+            Message::CancelLayoutEdits => {
+                return self
+                    .layout_editor_window
+                    .map_or_else(Task::none, window::close);
+            }
             Message::ErrorOccurred { title, error } => {
                 return Task::future(
                     rfd::AsyncMessageDialog::new()
@@ -406,6 +1276,9 @@ impl App {
             WindowType::Main => "LiveSplit".into(),
             WindowType::Settings => "Settings | LiveSplit".into(),
             WindowType::EditSplits => "Edit Splits | LiveSplit".into(),
+            WindowType::LayoutEditor => "Layout Editor | LiveSplit".into(),
+            WindowType::AttemptHistory => "Attempt History | LiveSplit".into(),
+            WindowType::SumOfBestCleaner => "Clean Sum of Best | LiveSplit".into(),
             WindowType::Untracked => panic!("Tried to get title of untracked window"),
         }
     }
@@ -417,21 +1290,108 @@ impl App {
             WindowType::Settings
         } else if self.edit_splits_window == Some(window) {
             WindowType::EditSplits
+        } else if self.layout_editor_window == Some(window) {
+            WindowType::LayoutEditor
+        } else if self.attempt_history_window == Some(window) {
+            WindowType::AttemptHistory
+        } else if self.sum_of_best_cleaner_window == Some(window) {
+            WindowType::SumOfBestCleaner
         } else {
             WindowType::Untracked
         }
     }
 
     pub fn subscription(&self) -> Subscription<Message> {
+        let server = if self.settings.server.enabled {
+            server::subscription(
+                self.settings.server,
+                self.livesplit_state.timer.clone(),
+                self.livesplit_state.reset_request(),
+            )
+        } else {
+            Subscription::none()
+        };
+
         Subscription::batch([
             iced::time::every(Duration::from_secs_f64(1. / 60.)).map(|_| Message::TimerTick),
             window::resize_events().map(|x| Message::WindowResized(x.0, x.1)),
             window::close_requests().map(Message::CloseRequested),
             window::close_events().map(Message::WindowClosed),
             event::listen_with(key_press_event_listener),
+            server,
         ])
     }
 
+    /// Switches to the auto splitter that goes with the current splits.
+    fn switch_auto_splitter(&self) -> Task<Message> {
+        let result = match self.settings.auto_splitter() {
+            Some(auto_splitter) => self
+                .livesplit_state
+                .auto_splitter
+                .load(&auto_splitter.module_path, &auto_splitter.user_settings),
+            None => self.livesplit_state.auto_splitter.unload(),
+        };
+
+        match result {
+            Ok(()) => Task::none(),
+            Err(e) => Task::done(Message::ErrorOccurred {
+                title: "Failed to load auto splitter".to_owned(),
+                error: e.to_string(),
+            }),
+        }
+    }
+
+    /// Stops the WebSocket server if it's running, then starts it again if it's enabled.
+    fn restart_websocket_server(&mut self) -> Task<Message> {
+        let shutdown_task = self
+            .websocket_server
+            .take()
+            .map_or_else(Task::none, WebSocketServer::shutdown);
+
+        if !self.settings.websocket.enabled {
+            return shutdown_task;
+        }
+
+        let (server, start_task) = WebSocketServer::start(
+            &self.settings.websocket,
+            self.livesplit_state.timer.clone(),
+            self.livesplit_state.reset_request(),
+        );
+        self.websocket_server = Some(server);
+
+        // the old listener has to let go of the port before the new one can bind it
+        shutdown_task.chain(start_task)
+    }
+
+    fn edit_attempt_history(&mut self, edit: impl FnOnce(&mut Run)) -> Task<Message> {
+        // the splits editor works on its own copy of the run, and pressing OK there would bring the attempts back
+        if self.splits_editor_state.is_some() {
+            return Task::done(Message::ErrorOccurred {
+                title: "Failed to edit attempt history".to_owned(),
+                error: "Close the splits editor before changing the attempt history.".to_owned(),
+            });
+        }
+
+        if let Err(e) = self.livesplit_state.edit_run(edit) {
+            return Task::done(Message::ErrorOccurred {
+                title: "Failed to edit attempt history".to_owned(),
+                error: e.to_string(),
+            });
+        }
+
+        if let Some(attempt_history_state) = self.attempt_history_state.as_mut() {
+            attempt_history_state.refresh(
+                self.livesplit_state
+                    .timer
+                    .read()
+                    .expect("Timer lock poisoned!")
+                    .run(),
+            );
+        }
+
+        Task::none()
+    }
+
     async fn get_load_splits_path() -> Message {
         match rfd::AsyncFileDialog::new()
             //.add_filter("LiveSplit Splits Files", &["*.lss"])
@@ -444,14 +1404,77 @@ impl App {
         }
     }
 
-    async fn get_save_splits_path(ct: Option<iced::task::Handle>) -> Message {
-        match rfd::AsyncFileDialog::new()
+    /// Asks where to save the splits. Splits read from another timer's file default to a `.lss` file next to it,
+    /// and the original file is refused.
+    async fn get_save_splits_path(
+        ct: Option<iced::task::Handle>,
+        foreign_splits: Option<(PathBuf, TimerKind<'static>)>,
+    ) -> Message {
+        let mut dialog = rfd::AsyncFileDialog::new()
             //.add_filter("LiveSplit Splits Files", &["*.lss"])
-            .set_title("Load splits")
+            .set_title("Save splits");
+        if let Some((original, _)) = &foreign_splits {
+            dialog = dialog
+                .add_filter("LiveSplit Splits Files", &["lss"])
+                .set_file_name(
+                    original
+                        .with_extension("lss")
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy(),
+                );
+            if let Some(directory) = original.parent() {
+                dialog = dialog.set_directory(directory);
+            }
+        }
+
+        let path = dialog.save_file().await.map(|path| path.path().to_owned());
+        match (path, foreign_splits) {
+            (Some(path), Some((original, kind))) if is_same_file(&path, &original) => {
+                if let Some(ct) = ct {
+                    ct.abort();
+                }
+                Message::ErrorOccurred {
+                    title: "Failed to save splits".to_owned(),
+                    error: format!(
+                        "Saving here would replace the {kind} file with LiveSplit splits. Please choose a different file."
+                    ),
+                }
+            }
+            (Some(path), _) => Message::SaveSplits(path),
+            (None, _) => {
+                if let Some(ct) = ct {
+                    ct.abort();
+                }
+                Message::None
+            }
+        }
+    }
+
+    async fn offer_save_as_livesplit(original: PathBuf, kind: TimerKind<'static>) -> Message {
+        let save = AsyncMessageDialog::new()
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .set_title("Save as LiveSplit?")
+            .set_description(format!(
+                "These splits were read from a {kind} file. They can only be saved as LiveSplit splits, so the original file will be left alone. Would you like to save them as LiveSplit splits now?"
+            ))
+            .show()
+            .await;
+        if save != MessageDialogResult::Yes {
+            return Message::None;
+        }
+
+        Self::get_save_splits_path(None, Some((original, kind))).await
+    }
+
+    async fn get_save_layout_path(ct: Option<iced::task::Handle>) -> Message {
+        match rfd::AsyncFileDialog::new()
+            .set_title("Save Layout")
+            .set_file_name("layout.ls1l")
             .save_file()
             .await
         {
-            Some(path) => Message::SaveSplits(path.path().to_owned()),
+            Some(path) => Message::SaveLayout(path.path().to_owned()),
             None => {
                 if let Some(ct) = ct {
                     ct.abort();
@@ -461,8 +1484,27 @@ impl App {
         }
     }
 
+    async fn pick_icon() -> Option<IconData> {
+        let file = rfd::AsyncFileDialog::new()
+            .add_filter(
+                "Images",
+                &["png", "jpg", "jpeg", "gif", "bmp", "ico", "webp"],
+            )
+            .set_title("Choose Icon")
+            .pick_file()
+            .await?;
+
+        Some(IconData(file.read().await))
+    }
+
     fn save_if_dirty(&self, ct: iced::task::Handle) -> Task<Message> {
+        self.save_splits_if_dirty(ct.clone())
+            .chain(self.save_layout_if_dirty(ct))
+    }
+
+    fn save_splits_if_dirty(&self, ct: iced::task::Handle) -> Task<Message> {
         if self.livesplit_state.is_dirty() {
+            let foreign_splits = self.foreign_splits.clone();
             Task::future(async {
                 match AsyncMessageDialog::new()
                     .set_buttons(rfd::MessageButtons::YesNoCancel)
@@ -472,7 +1514,7 @@ impl App {
                     .await
                 {
                     rfd::MessageDialogResult::Yes => {
-                        Some(Self::get_save_splits_path(Some(ct)).await)
+                        Some(Self::get_save_splits_path(Some(ct), foreign_splits).await)
                     }
                     rfd::MessageDialogResult::No => None,
                     rfd::MessageDialogResult::Cancel => {
@@ -487,6 +1529,44 @@ impl App {
             Task::none()
         }
     }
+
+    fn save_layout_if_dirty(&self, ct: iced::task::Handle) -> Task<Message> {
+        if self.livesplit_state.is_layout_dirty() {
+            let layout_path = self.settings.layout_path.clone();
+            Task::future(async move {
+                // the splits prompt may already have cancelled whatever we're saving for
+                if ct.is_aborted() {
+                    return None;
+                }
+
+                match AsyncMessageDialog::new()
+                    .set_buttons(rfd::MessageButtons::YesNoCancel)
+                    .set_title("Save layout?")
+                    .set_description("Your layout has been modified. Would you like to save it?")
+                    .show()
+                    .await
+                {
+                    rfd::MessageDialogResult::Yes => match layout_path {
+                        Some(path) => Some(Message::SaveLayout(path)),
+                        None => Some(Self::get_save_layout_path(Some(ct)).await),
+                    },
+                    rfd::MessageDialogResult::No => None,
+                    rfd::MessageDialogResult::Cancel => {
+                        ct.abort();
+                        None
+                    }
+                    _ => unreachable!(),
+                }
+            })
+            .and_then(Task::done)
+        } else {
+            Task::none()
+        }
+    }
+}
+
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    a == b || fs::canonicalize(a).is_ok_and(|a| fs::canonicalize(b).ok() == Some(a))
 }
 
 fn key_press_event_listener(
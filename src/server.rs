@@ -0,0 +1,237 @@
+//! A TCP server speaking the text protocol of the original LiveSplit Server component, so that stream tooling and
+//! load removers written for it can drive this timer too. Every command is a single line, and only the `get*`
+//! commands (and `ping`) send a line back.
+
+use std::time::Duration;
+
+use iced::{Subscription, futures::SinkExt};
+use livesplit_core::{
+    SharedTimer, TimeSpan, Timer, TimerPhase, TimingMethod,
+    analysis::state_helper,
+    timing::formatter::{self, Accuracy, Delta, Regular, TimeFormatter},
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    task::JoinSet,
+};
+
+use crate::{
+    Message,
+    app_settings::ServerSettings,
+    livesplit_state::{ResetRequest, set_game_time_paused},
+};
+
+/// How long to wait before accepting connections again after it failed.
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+pub fn subscription(
+    settings: ServerSettings,
+    timer: SharedTimer,
+    reset_request: ResetRequest,
+) -> Subscription<Message> {
+    let ServerSettings {
+        port, allow_lan, ..
+    } = settings;
+
+    // the id covers everything the listener depends on, so changing either restarts the server
+    Subscription::run_with_id(
+        ("livesplit-server", port, allow_lan),
+        iced::stream::channel(1, move |mut output| async move {
+            let address = if allow_lan { "0.0.0.0" } else { "127.0.0.1" };
+            let listener = match TcpListener::bind((address, port)).await {
+                Ok(listener) => listener,
+                Err(e) => {
+                    output
+                        .send(Message::ErrorOccurred {
+                            title: "Failed to start LiveSplit Server".to_owned(),
+                            error: format!("Could not listen on port {port}: {e}"),
+                        })
+                        .await
+                        .ok();
+                    return;
+                }
+            };
+
+            // Connections live in here so they're all dropped along with the subscription.
+            let mut connections = JoinSet::new();
+
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        connections.spawn(handle_connection(
+                            stream,
+                            timer.clone(),
+                            reset_request.clone(),
+                        ));
+                    }
+                    // usually running out of file descriptors, which won't clear up if we retry straight away
+                    Err(e) => {
+                        eprintln!("LiveSplit Server failed to accept a connection: {e}");
+                        tokio::time::sleep(ACCEPT_RETRY_DELAY).await;
+                    }
+                }
+                while connections.try_join_next().is_some() {}
+            }
+        }),
+    )
+}
+
+async fn handle_connection(stream: TcpStream, timer: SharedTimer, reset_request: ResetRequest) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        let response = {
+            let mut timer = timer.write().expect("Timer lock poisoned!");
+            handle_command(&mut timer, &reset_request, line.trim())
+        };
+
+        if let Some(response) = response
+            && writer
+                .write_all(format!("{response}\r\n").as_bytes())
+                .await
+                .is_err()
+        {
+            return;
+        }
+    }
+}
+
+fn handle_command(timer: &mut Timer, reset_request: &ResetRequest, line: &str) -> Option<String> {
+    let (command, args) = line.split_once(' ').unwrap_or((line, ""));
+    let method = timer.current_timing_method();
+
+    match command {
+        "starttimer" => timer.start(),
+        "startorsplit" => timer.split_or_start(),
+        "split" => timer.split(),
+        "unsplit" => timer.undo_split(),
+        "skipsplit" => timer.skip_split(),
+        "pause" => timer.pause(),
+        "resume" => timer.resume(),
+        // goes through the app so it can ask whether to update the splits
+        "reset" => reset_request.request(),
+        "initgametime" => timer.initialize_game_time(),
+        "setgametime" => {
+            if let Ok(time) = args.parse() {
+                timer.set_game_time(time);
+            }
+        }
+        "setloadingtimes" => {
+            if let Ok(time) = args.parse() {
+                timer.set_loading_times(time);
+            }
+        }
+        "pausegametime" => set_game_time_paused(timer, true),
+        "unpausegametime" => set_game_time_paused(timer, false),
+        "setcomparison" => {
+            timer.set_current_comparison(args).ok();
+        }
+        "switchto" => match args {
+            "realtime" => timer.set_current_timing_method(TimingMethod::RealTime),
+            "gametime" => timer.set_current_timing_method(TimingMethod::GameTime),
+            _ => {}
+        },
+        "getdelta" => {
+            let comparison = comparison_or_current(timer, args);
+            let segments = timer.run().segments();
+            let delta = match (timer.current_phase(), timer.current_split_index()) {
+                (TimerPhase::Running | TimerPhase::Paused, Some(index)) => {
+                    state_helper::last_delta(timer.run(), index, &comparison, method)
+                }
+                (TimerPhase::Ended, _) => segments.last().and_then(|last| {
+                    Some(last.split_time()[method]? - last.comparison(&comparison)[method]?)
+                }),
+                _ => None,
+            };
+            return Some(ascii(Delta::with_decimal_dropping().format(delta)));
+        }
+        "getsplitindex" => {
+            return Some(
+                timer
+                    .current_split_index()
+                    .map_or(-1, |index| index as i64)
+                    .to_string(),
+            );
+        }
+        "getcurrentsplitname" => {
+            return Some(timer.current_split().map_or("-", |s| s.name()).to_owned());
+        }
+        "getprevioussplitname" => {
+            return Some(
+                previous_split(timer)
+                    .map_or("-", |index| timer.run().segment(index).name())
+                    .to_owned(),
+            );
+        }
+        "getlastsplittime" => {
+            let time = previous_split(timer)
+                .and_then(|index| timer.run().segment(index).split_time()[method]);
+            return Some(format_time(time));
+        }
+        "getcomparisonsplittime" => {
+            let time = timer
+                .current_split()
+                .and_then(|s| s.comparison(timer.current_comparison())[method]);
+            return Some(format_time(time));
+        }
+        "getcurrenttime" => return Some(format_time(timer.snapshot().current_time()[method])),
+        "getcurrentrealtime" => {
+            return Some(format_time(timer.snapshot().current_time().real_time));
+        }
+        "getcurrentgametime" => {
+            return Some(format_time(timer.snapshot().current_time().game_time));
+        }
+        "getfinaltime" => {
+            let comparison = comparison_or_current(timer, args);
+            let last = timer.run().segments().last();
+            let time = if timer.current_phase() == TimerPhase::Ended {
+                last.and_then(|s| s.split_time()[method])
+            } else {
+                last.and_then(|s| s.comparison(&comparison)[method])
+            };
+            return Some(format_time(time));
+        }
+        "getcurrenttimerphase" => {
+            return Some(
+                match timer.current_phase() {
+                    TimerPhase::NotRunning => "NotRunning",
+                    TimerPhase::Running => "Running",
+                    TimerPhase::Ended => "Ended",
+                    TimerPhase::Paused => "Paused",
+                }
+                .to_owned(),
+            );
+        }
+        "getattemptcount" => return Some(timer.run().attempt_count().to_string()),
+        "ping" => return Some("pong".to_owned()),
+        _ => {}
+    }
+
+    None
+}
+
+fn comparison_or_current(timer: &Timer, args: &str) -> String {
+    if args.is_empty() {
+        timer.current_comparison().to_owned()
+    } else {
+        args.to_owned()
+    }
+}
+
+fn previous_split(timer: &Timer) -> Option<usize> {
+    timer.current_split_index()?.checked_sub(1)
+}
+
+fn format_time(time: Option<TimeSpan>) -> String {
+    ascii(Regular::with_accuracy(Accuracy::Hundredths).format(time))
+}
+
+// Clients of the original server expect plain ASCII dashes rather than the typographic ones we render with.
+fn ascii(formatted: impl std::fmt::Display) -> String {
+    formatted
+        .to_string()
+        .replace(formatter::MINUS, formatter::ASCII_MINUS)
+        .replace(formatter::DASH, formatter::ASCII_MINUS)
+}
@@ -0,0 +1,205 @@
+use std::{cmp::Ordering, collections::BTreeSet, fmt};
+
+use livesplit_core::{AtomicDateTime, Attempt, Run, TimeSpan};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortColumn {
+    Index,
+    Started,
+    Ended,
+    RealTime,
+    GameTime,
+    PauseTime,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AttemptFilter {
+    #[default]
+    All,
+    Finished,
+    Reset,
+}
+
+impl AttemptFilter {
+    pub const ALL: [Self; 3] = [Self::All, Self::Finished, Self::Reset];
+
+    fn matches(self, attempt: &Attempt) -> bool {
+        match self {
+            Self::All => true,
+            Self::Finished => is_finished(attempt),
+            Self::Reset => !is_finished(attempt),
+        }
+    }
+}
+
+impl fmt::Display for AttemptFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::All => "All Attempts",
+            Self::Finished => "Finished",
+            Self::Reset => "Reset",
+        })
+    }
+}
+
+pub struct AttemptHistoryState {
+    pub attempts: Vec<Attempt>,
+    /// Indices into `attempts` that pass the filter, in display order.
+    pub visible: Vec<usize>,
+    pub sort_column: SortColumn,
+    pub ascending: bool,
+    pub filter: AttemptFilter,
+    /// Attempts chosen for deletion, by their attempt index.
+    pub selected: BTreeSet<i32>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Message {
+    SortBy(SortColumn),
+    SetFilter(AttemptFilter),
+    ToggleSelected(i32, bool),
+    SelectAllVisible,
+    SelectNone,
+}
+
+impl Message {
+    pub fn into_app_message(self) -> crate::Message {
+        crate::Message::AttemptHistoryMessage(self)
+    }
+}
+
+impl AttemptHistoryState {
+    pub fn new(run: &Run) -> Self {
+        let mut me = Self {
+            attempts: vec![],
+            visible: vec![],
+            sort_column: SortColumn::Index,
+            ascending: false,
+            filter: AttemptFilter::All,
+            selected: BTreeSet::new(),
+        };
+
+        me.refresh(run);
+
+        me
+    }
+
+    /// Picks up attempts added or removed since the window was opened.
+    pub fn refresh(&mut self, run: &Run) {
+        self.attempts = run.attempt_history().to_vec();
+        self.selected
+            .retain(|index| self.attempts.iter().any(|a| a.index() == *index));
+        self.update_visible();
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::SortBy(column) => {
+                if self.sort_column == column {
+                    self.ascending = !self.ascending;
+                } else {
+                    self.sort_column = column;
+                    self.ascending = true;
+                }
+                self.update_visible();
+            }
+            Message::SetFilter(filter) => {
+                self.filter = filter;
+                self.update_visible();
+            }
+            Message::ToggleSelected(index, selected) => {
+                if selected {
+                    self.selected.insert(index);
+                } else {
+                    self.selected.remove(&index);
+                }
+            }
+            Message::SelectAllVisible => {
+                self.selected
+                    .extend(self.visible.iter().map(|&i| self.attempts[i].index()));
+            }
+            Message::SelectNone => self.selected.clear(),
+        }
+    }
+
+    fn update_visible(&mut self) {
+        let attempts = &self.attempts;
+
+        self.visible = (0..attempts.len())
+            .filter(|&i| self.filter.matches(&attempts[i]))
+            .collect();
+
+        let column = self.sort_column;
+        self.visible.sort_by(|&a, &b| {
+            let (a, b) = (&attempts[a], &attempts[b]);
+            match column {
+                SortColumn::Index => a.index().cmp(&b.index()),
+                SortColumn::Started => cmp_date(a.started(), b.started()),
+                SortColumn::Ended => cmp_date(a.ended(), b.ended()),
+                SortColumn::RealTime => cmp_time(a.time().real_time, b.time().real_time),
+                SortColumn::GameTime => cmp_time(a.time().game_time, b.time().game_time),
+                SortColumn::PauseTime => cmp_time(a.pause_time(), b.pause_time()),
+            }
+        });
+
+        if !self.ascending {
+            self.visible.reverse();
+        }
+    }
+}
+
+/// Attempts only get a time if they were run to the end.
+pub const fn is_finished(attempt: &Attempt) -> bool {
+    attempt.time().real_time.is_some()
+}
+
+/// Removes the attempts with the given indices along with the segment times recorded during them.
+pub fn remove_attempts(run: &mut Run, indices: &BTreeSet<i32>) {
+    // The run only lets us append to the attempt history, so clear it out and put back everything we keep.
+    let kept: Vec<Attempt> = run
+        .attempt_history()
+        .iter()
+        .filter(|attempt| !indices.contains(&attempt.index()))
+        .cloned()
+        .collect();
+    let segment_histories: Vec<_> = run
+        .segments()
+        .iter()
+        .map(|segment| segment.segment_history().clone())
+        .collect();
+
+    run.clear_history();
+
+    for attempt in kept {
+        run.add_attempt_with_index(
+            attempt.time(),
+            attempt.index(),
+            attempt.started(),
+            attempt.ended(),
+            attempt.pause_time(),
+        );
+    }
+    for (segment, mut history) in run.segments_mut().iter_mut().zip(segment_histories) {
+        history.retain(|&(index, _)| !indices.contains(&index));
+        *segment.segment_history_mut() = history;
+    }
+}
+
+// Missing values sort as if they were larger than any present one.
+fn cmp_time(a: Option<TimeSpan>, b: Option<TimeSpan>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+fn cmp_date(a: Option<AtomicDateTime>, b: Option<AtomicDateTime>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.time.cmp(&b.time),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
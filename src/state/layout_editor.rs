@@ -0,0 +1,360 @@
+use std::{collections::HashMap, fmt};
+
+use livesplit_core::{
+    Component, LayoutEditor,
+    component::{
+        blank_space, current_comparison, current_pace, delta, detailed_timer, graph, pb_chance,
+        possible_time_save, previous_segment, segment_time, separator, splits, sum_of_best, text,
+        timer, title, total_playtime,
+    },
+    layout::editor,
+    settings::{Color, Font, Gradient, ListGradient, Value},
+};
+
+use crate::livesplit_state::LivesplitState;
+
+pub struct LayoutEditorState {
+    pub editor: LayoutEditor,
+    pub editor_state: editor::State,
+    pub settings_tab: SettingsGroup,
+    pub component_to_add: Option<ComponentKind>,
+    // Text buffers for fields that have to be parsed (numbers, colors, fonts), keyed by group, field index and the
+    // sub-field within it (gradients have two colors). Dropped on blur so the field shows the canonical value again.
+    pub field_buffers: HashMap<(SettingsGroup, usize, usize), String>,
+    modified: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SettingsGroup {
+    Component,
+    General,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ComponentKind {
+    BlankSpace,
+    CurrentComparison,
+    CurrentPace,
+    Delta,
+    DetailedTimer,
+    Graph,
+    PbChance,
+    PossibleTimeSave,
+    PreviousSegment,
+    SegmentTime,
+    Separator,
+    Splits,
+    SumOfBest,
+    Text,
+    Timer,
+    Title,
+    TotalPlaytime,
+}
+
+impl ComponentKind {
+    pub const ALL: [Self; 17] = [
+        Self::BlankSpace,
+        Self::CurrentComparison,
+        Self::CurrentPace,
+        Self::Delta,
+        Self::DetailedTimer,
+        Self::Graph,
+        Self::PbChance,
+        Self::PossibleTimeSave,
+        Self::PreviousSegment,
+        Self::SegmentTime,
+        Self::Separator,
+        Self::Splits,
+        Self::SumOfBest,
+        Self::Text,
+        Self::Timer,
+        Self::Title,
+        Self::TotalPlaytime,
+    ];
+
+    fn create(self) -> Component {
+        match self {
+            Self::BlankSpace => blank_space::Component::new().into(),
+            Self::CurrentComparison => current_comparison::Component::new().into(),
+            Self::CurrentPace => current_pace::Component::new().into(),
+            Self::Delta => delta::Component::new().into(),
+            Self::DetailedTimer => Box::new(detailed_timer::Component::new()).into(),
+            Self::Graph => graph::Component::new().into(),
+            Self::PbChance => pb_chance::Component::new().into(),
+            Self::PossibleTimeSave => possible_time_save::Component::new().into(),
+            Self::PreviousSegment => previous_segment::Component::new().into(),
+            Self::SegmentTime => segment_time::Component::new().into(),
+            Self::Separator => separator::Component::new().into(),
+            Self::Splits => splits::Component::new().into(),
+            Self::SumOfBest => sum_of_best::Component::new().into(),
+            Self::Text => text::Component::new().into(),
+            Self::Timer => timer::Component::new().into(),
+            Self::Title => title::Component::new().into(),
+            Self::TotalPlaytime => total_playtime::Component::new().into(),
+        }
+    }
+}
+
+impl fmt::Display for ComponentKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::BlankSpace => "Blank Space",
+            Self::CurrentComparison => "Current Comparison",
+            Self::CurrentPace => "Current Pace",
+            Self::Delta => "Delta",
+            Self::DetailedTimer => "Detailed Timer",
+            Self::Graph => "Graph",
+            Self::PbChance => "PB Chance",
+            Self::PossibleTimeSave => "Possible Time Save",
+            Self::PreviousSegment => "Previous Segment",
+            Self::SegmentTime => "Segment Time",
+            Self::Separator => "Separator",
+            Self::Splits => "Splits",
+            Self::SumOfBest => "Sum of Best",
+            Self::Text => "Text",
+            Self::Timer => "Timer",
+            Self::Title => "Title",
+            Self::TotalPlaytime => "Total Playtime",
+        })
+    }
+}
+
+/// `settings::Value` doesn't implement `Debug`, which every message needs.
+#[derive(Clone)]
+pub struct SettingValue(pub Value);
+
+impl fmt::Debug for SettingValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match serde_json::to_string(&self.0) {
+            Ok(json) => f.write_str(&json),
+            Err(_) => f.write_str("<setting value>"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Message {
+    SelectComponent(usize),
+    SelectSettingsTab(SettingsGroup),
+    ComponentToAddSelected(ComponentKind),
+    AddComponentClicked,
+    RemoveComponentClicked,
+    DuplicateComponentClicked,
+    MoveUpClicked,
+    MoveDownClicked,
+
+    SetValue(SettingsGroup, usize, SettingValue),
+    UpdateFieldBuffer(SettingsGroup, usize, usize, String),
+    FieldBlur(SettingsGroup, usize, usize),
+}
+
+impl Message {
+    pub fn into_app_message(self) -> crate::Message {
+        crate::Message::LayoutEditorMessage(self)
+    }
+}
+
+impl LayoutEditorState {
+    pub fn new(livesplit_state: &LivesplitState) -> Result<Self, editor::Error> {
+        let editor = LayoutEditor::new(livesplit_state.layout().clone())?;
+        let editor_state = editor.state();
+
+        Ok(Self {
+            editor,
+            editor_state,
+            settings_tab: SettingsGroup::Component,
+            component_to_add: None,
+            field_buffers: HashMap::new(),
+            modified: false,
+        })
+    }
+
+    /// Pushes the layout being edited to the main window while keeping the editor open.
+    pub fn apply(&mut self, livesplit_state: &mut LivesplitState) {
+        if !self.modified {
+            return;
+        }
+
+        // The editor doesn't hand out its layout without closing, so close it and reopen on a copy.
+        let selected = self.editor_state.selected_component as usize;
+        let placeholder = LayoutEditor::new(livesplit_core::Layout::default_layout())
+            .expect("The default layout has components");
+        let layout = std::mem::replace(&mut self.editor, placeholder).close();
+
+        livesplit_state.set_layout(layout.clone());
+
+        self.editor = LayoutEditor::new(layout).expect(
+            "The layout editor never removes the last component, so the layout can't be empty",
+        );
+        self.editor.select(selected);
+        self.editor_state = self.editor.state();
+        self.modified = false;
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::SelectComponent(index) => {
+                self.editor.select(index);
+                self.field_buffers.clear();
+            }
+            Message::SelectSettingsTab(tab) => self.settings_tab = tab,
+            Message::ComponentToAddSelected(kind) => self.component_to_add = Some(kind),
+            Message::AddComponentClicked => {
+                if let Some(kind) = self.component_to_add {
+                    self.editor.add_component(kind.create());
+                    self.field_buffers.clear();
+                    self.modified = true;
+                }
+            }
+            Message::RemoveComponentClicked => {
+                self.editor.remove_component();
+                self.field_buffers.clear();
+                self.modified = true;
+            }
+            Message::DuplicateComponentClicked => {
+                self.editor.duplicate_component();
+                self.field_buffers.clear();
+                self.modified = true;
+            }
+            Message::MoveUpClicked => {
+                if self.editor_state.buttons.can_move_up {
+                    self.editor.move_component_up();
+                    self.modified = true;
+                }
+            }
+            Message::MoveDownClicked => {
+                if self.editor_state.buttons.can_move_down {
+                    self.editor.move_component_down();
+                    self.modified = true;
+                }
+            }
+            Message::SetValue(group, index, SettingValue(value)) => {
+                self.set_value(group, index, value);
+            }
+            Message::UpdateFieldBuffer(group, index, slot, text) => {
+                let current = &self.description(group).fields[index].value;
+                if let Some(value) = parse_field(current, slot, &text) {
+                    self.set_value(group, index, value);
+                }
+                self.field_buffers.insert((group, index, slot), text);
+            }
+            Message::FieldBlur(group, index, slot) => {
+                self.field_buffers.remove(&(group, index, slot));
+            }
+        }
+        self.editor_state = self.editor.state();
+    }
+
+    pub const fn description(
+        &self,
+        group: SettingsGroup,
+    ) -> &livesplit_core::settings::SettingsDescription {
+        match group {
+            SettingsGroup::Component => &self.editor_state.component_settings,
+            SettingsGroup::General => &self.editor_state.general_settings,
+        }
+    }
+
+    fn set_value(&mut self, group: SettingsGroup, index: usize, value: Value) {
+        self.modified = true;
+        match group {
+            SettingsGroup::Component => self.editor.set_component_settings_value(index, value),
+            SettingsGroup::General => self.editor.set_general_settings_value(index, value),
+        }
+    }
+}
+
+/// The text shown for a free-form part of a setting, i.e. anything that isn't a checkbox or a pick list.
+pub fn field_text(value: &Value, slot: usize) -> String {
+    match value {
+        Value::UInt(v) => v.to_string(),
+        Value::Int(v) => v.to_string(),
+        Value::Color(c) => color_to_hex(*c),
+        Value::OptionalColor(c) => c.map(color_to_hex).unwrap_or_default(),
+        Value::Font(font) => font.as_ref().map(|f| f.family.clone()).unwrap_or_default(),
+        Value::Gradient(g) => gradient_colors(*g)[slot]
+            .map(color_to_hex)
+            .unwrap_or_default(),
+        Value::ListGradient(ListGradient::Same(g)) => gradient_colors(*g)[slot]
+            .map(color_to_hex)
+            .unwrap_or_default(),
+        Value::ListGradient(ListGradient::Alternating(even, odd)) => {
+            color_to_hex([*even, *odd][slot])
+        }
+        Value::DeltaGradient(timer::DeltaGradient::Gradient(g)) => gradient_colors(*g)[slot]
+            .map(color_to_hex)
+            .unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+/// Parses a free-form part of a setting back into a full value, based on the setting's current value.
+fn parse_field(current: &Value, slot: usize, text: &str) -> Option<Value> {
+    match current {
+        Value::UInt(_) => text.parse().ok().map(Value::UInt),
+        Value::Int(_) => text.parse().ok().map(Value::Int),
+        Value::Color(_) => parse_hex_color(text).map(Value::Color),
+        Value::OptionalColor(_) if text.is_empty() => Some(Value::OptionalColor(None)),
+        Value::OptionalColor(_) => parse_hex_color(text).map(|c| Value::OptionalColor(Some(c))),
+        Value::Font(_) if text.is_empty() => Some(Value::Font(None)),
+        Value::Font(font) => Some(Value::Font(Some(Font {
+            family: text.to_owned(),
+            ..font.clone().unwrap_or_default()
+        }))),
+        Value::Gradient(g) => {
+            with_gradient_color(*g, slot, parse_hex_color(text)?).map(Value::Gradient)
+        }
+        Value::ListGradient(ListGradient::Same(g)) => {
+            with_gradient_color(*g, slot, parse_hex_color(text)?)
+                .map(|g| Value::ListGradient(ListGradient::Same(g)))
+        }
+        Value::ListGradient(ListGradient::Alternating(even, odd)) => {
+            let color = parse_hex_color(text)?;
+            Some(Value::ListGradient(if slot == 0 {
+                ListGradient::Alternating(color, *odd)
+            } else {
+                ListGradient::Alternating(*even, color)
+            }))
+        }
+        Value::DeltaGradient(timer::DeltaGradient::Gradient(g)) => {
+            with_gradient_color(*g, slot, parse_hex_color(text)?)
+                .map(|g| Value::DeltaGradient(timer::DeltaGradient::Gradient(g)))
+        }
+        _ => None,
+    }
+}
+
+pub const fn gradient_colors(gradient: Gradient) -> [Option<Color>; 2] {
+    match gradient {
+        Gradient::Transparent => [None, None],
+        Gradient::Plain(c) => [Some(c), None],
+        Gradient::Vertical(a, b) | Gradient::Horizontal(a, b) => [Some(a), Some(b)],
+    }
+}
+
+const fn with_gradient_color(gradient: Gradient, slot: usize, color: Color) -> Option<Gradient> {
+    match (gradient, slot) {
+        (Gradient::Plain(_), 0) => Some(Gradient::Plain(color)),
+        (Gradient::Vertical(_, b), 0) => Some(Gradient::Vertical(color, b)),
+        (Gradient::Vertical(a, _), 1) => Some(Gradient::Vertical(a, color)),
+        (Gradient::Horizontal(_, b), 0) => Some(Gradient::Horizontal(color, b)),
+        (Gradient::Horizontal(a, _), 1) => Some(Gradient::Horizontal(a, color)),
+        _ => None,
+    }
+}
+
+fn color_to_hex(color: Color) -> String {
+    let [r, g, b, a] = color.to_rgba8();
+    format!("#{r:02X}{g:02X}{b:02X}{a:02X}")
+}
+
+fn parse_hex_color(text: &str) -> Option<Color> {
+    let hex = text.strip_prefix('#').unwrap_or(text);
+    if !hex.is_ascii() || (hex.len() != 6 && hex.len() != 8) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    let alpha = if hex.len() == 8 { channel(6)? } else { 255 };
+
+    Some(Color::rgba8(channel(0)?, channel(2)?, channel(4)?, alpha))
+}
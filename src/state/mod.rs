@@ -1 +1,5 @@
+pub mod attempt_history;
+pub mod layout_editor;
+pub mod segment_history;
 pub mod splits_editor;
+pub mod sum_of_best_cleaner;
@@ -0,0 +1,106 @@
+//! The recorded history of a single segment, and the statistics the splits editor shows for it.
+
+use livesplit_core::{Run, TimeSpan, TimingMethod};
+
+pub struct SegmentHistoryView {
+    pub segment_index: usize,
+    /// Every recorded attempt at the segment as (attempt id, segment time). Skipped splits have no time.
+    pub entries: Vec<(i32, Option<TimeSpan>)>,
+    pub stats: SegmentStats,
+}
+
+#[derive(Default)]
+pub struct SegmentStats {
+    pub mean: Option<TimeSpan>,
+    pub median: Option<TimeSpan>,
+    pub std_dev: Option<TimeSpan>,
+    /// How many times the segment was the fastest it had been so far.
+    pub gold_count: usize,
+    /// The share of attempts that reached the segment and were reset during it.
+    pub reset_rate: Option<f64>,
+}
+
+impl SegmentHistoryView {
+    pub fn new(run: &Run, segment_index: usize, method: TimingMethod) -> Self {
+        let mut entries: Vec<_> = run
+            .segment(segment_index)
+            .segment_history()
+            .iter()
+            .map(|&(id, time)| (id, time[method]))
+            .collect();
+        entries.sort_by_key(|&(id, _)| id);
+
+        let stats = SegmentStats::new(run, segment_index, &entries);
+
+        Self {
+            segment_index,
+            entries,
+            stats,
+        }
+    }
+}
+
+impl SegmentStats {
+    fn new(run: &Run, segment_index: usize, entries: &[(i32, Option<TimeSpan>)]) -> Self {
+        let mut seconds: Vec<f64> = entries
+            .iter()
+            .filter_map(|&(_, time)| Some(time?.total_seconds()))
+            .collect();
+
+        if seconds.is_empty() {
+            return Self {
+                reset_rate: reset_rate(run, segment_index),
+                ..Default::default()
+            };
+        }
+
+        let count = seconds.len() as f64;
+        let mean = seconds.iter().sum::<f64>() / count;
+        let variance = seconds.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / count;
+
+        seconds.sort_by(f64::total_cmp);
+        let middle = seconds.len() / 2;
+        let median = if seconds.len().is_multiple_of(2) {
+            (seconds[middle - 1] + seconds[middle]) / 2.
+        } else {
+            seconds[middle]
+        };
+
+        // entries are in attempt order, so this replays how the best segment improved over time
+        let mut best = None;
+        let mut gold_count = 0;
+        for time in entries.iter().filter_map(|&(_, time)| time) {
+            if best.is_none_or(|best| time < best) {
+                best = Some(time);
+                gold_count += 1;
+            }
+        }
+
+        Self {
+            mean: Some(TimeSpan::from_seconds(mean)),
+            median: Some(TimeSpan::from_seconds(median)),
+            std_dev: Some(TimeSpan::from_seconds(variance.sqrt())),
+            gold_count,
+            reset_rate: reset_rate(run, segment_index),
+        }
+    }
+}
+
+fn reset_rate(run: &Run, segment_index: usize) -> Option<f64> {
+    // ids below 1 belong to history that isn't attached to an attempt, e.g. imported from another timer
+    let attempts = |history: &livesplit_core::SegmentHistory| {
+        history.iter().filter(|&&(id, _)| id >= 1).count()
+    };
+
+    let reached = match segment_index {
+        0 => run
+            .attempt_history()
+            .iter()
+            .filter(|attempt| attempt.index() >= 1)
+            .count(),
+        _ => attempts(run.segment(segment_index - 1).segment_history()),
+    };
+    let completed = attempts(run.segment(segment_index).segment_history());
+
+    (reached > 0).then(|| reached.saturating_sub(completed) as f64 / reached as f64)
+}
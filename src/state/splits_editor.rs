@@ -1,6 +1,16 @@
-use livesplit_core::{RunEditor, run::editor};
+use std::{fmt, mem};
 
-use crate::livesplit_state::LivesplitState;
+use iced::widget::image;
+use livesplit_core::{
+    Run, RunEditor, TimingMethod, comparison,
+    run::editor::{self, SegmentRow, SelectionState},
+    settings::Image,
+};
+
+use crate::{
+    livesplit_state::LivesplitState,
+    state::{segment_history::SegmentHistoryView, sum_of_best_cleaner::SumOfBestCleanerState},
+};
 
 pub struct SplitsEditorState {
     pub offset_buffer: String,
@@ -9,6 +19,56 @@ pub struct SplitsEditorState {
     pub segment_time_buffers: Vec<String>,
     pub split_time_buffers: Vec<String>,
     pub best_segment_time_buffers: Vec<String>,
+
+    // kept around so the images aren't decoded again every frame
+    pub game_icon: Option<image::Handle>,
+    pub segment_icons: Vec<Option<image::Handle>>,
+
+    /// Index into `editor_state.comparison_names` of the comparison shown in the grid.
+    pub selected_comparison: Option<usize>,
+    pub comparison_time_buffers: Vec<String>,
+    pub comparison_name_buffer: String,
+    pub comparison_error: Option<String>,
+    pub goal_time_buffer: String,
+
+    pub new_speedrun_com_variable_name: String,
+    pub new_custom_variable_name: String,
+
+    /// The history of the active segment.
+    pub segment_history: Option<SegmentHistoryView>,
+
+    undo_stack: Vec<Run>,
+    redo_stack: Vec<Run>,
+    /// Bumped on every undo step, undo and redo, so others can tell whether the run changed under them.
+    revision: usize,
+    /// The field currently being typed into, so that a run of keystrokes becomes a single undo step.
+    typing_in: Option<TypingField>,
+}
+
+/// A text field in the editor, told apart by the message it sends and its row.
+type TypingField = (mem::Discriminant<Message>, usize);
+
+// snapshots hold the whole run including its history, so don't let them pile up forever
+const MAX_UNDO_STEPS: usize = 100;
+
+/// How a message changes the run, which decides whether it gets its own undo step.
+enum EditKind {
+    /// Doesn't touch the run.
+    None,
+    /// A single action, always its own step.
+    Discrete,
+    /// Typing into a field. Consecutive keystrokes into the same field are merged into one step.
+    Typing(usize),
+}
+
+/// The raw bytes of an image file picked as an icon.
+#[derive(Clone)]
+pub struct IconData(pub Vec<u8>);
+
+impl fmt::Debug for IconData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<{} bytes>", self.0.len())
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -16,11 +76,15 @@ pub enum Message {
     UpdateGameName(String),
     UpdateCategoryName(String),
     UpdateNumAttempts(String),
+    SetGameIcon(IconData),
+    RemoveGameIcon,
     UpdateOffsetBuffer(String),
     OffsetTextboxBlur,
     SelectRow(usize),
 
     UpdateSegmentName(String),
+    SetSegmentIcon(usize, IconData),
+    RemoveSegmentIcon(usize),
     SplitTimeBlur(usize),
     SegmentTimeBlur(usize),
     BestSegmentTimeBlur(usize),
@@ -33,14 +97,98 @@ pub enum Message {
     RemoveSegmentClicked,
     MoveUpClicked,
     MoveDownClicked,
+
+    SelectTimingMethod(TimingMethod),
+    SelectComparison(usize),
+    UpdateComparisonNameBuffer(String),
+    AddComparisonClicked,
+    RenameComparisonClicked,
+    RemoveComparisonClicked,
+    MoveComparisonUpClicked,
+    MoveComparisonDownClicked,
+    UpdateGoalTimeBuffer(String),
+    GenerateGoalClicked,
+    UpdateComparisonTimeBuffer(String, usize),
+    ComparisonTimeBlur(usize),
+
+    UpdatePlatform(String),
+    UpdateRegion(String),
+    SetUsesEmulator(bool),
+    UpdateSpeedrunComVariable(usize, String),
+    RemoveSpeedrunComVariable(usize),
+    UpdateNewSpeedrunComVariableName(String),
+    AddSpeedrunComVariableClicked,
+    UpdateCustomVariable(usize, String),
+    RemoveCustomVariable(usize),
+    UpdateNewCustomVariableName(String),
+    AddCustomVariableClicked,
+
+    RemoveSegmentHistoryEntry(usize, i32),
+
+    Undo,
+    Redo,
 }
 
 impl Message {
     pub fn into_app_message(self) -> crate::Message {
         crate::Message::SplitsEditorMessage(self)
     }
+
+    const fn edit_kind(&self) -> EditKind {
+        match self {
+            Self::UpdateGameName(_)
+            | Self::UpdateCategoryName(_)
+            | Self::UpdateNumAttempts(_)
+            | Self::UpdateSegmentName(_)
+            | Self::UpdatePlatform(_)
+            | Self::UpdateRegion(_) => EditKind::Typing(0),
+            Self::UpdateSplitTimeBuffer(_, idx)
+            | Self::UpdateSegmentTimeBuffer(_, idx)
+            | Self::UpdateBestSegmentTimeBuffer(_, idx)
+            | Self::UpdateComparisonTimeBuffer(_, idx)
+            | Self::UpdateSpeedrunComVariable(idx, _)
+            | Self::UpdateCustomVariable(idx, _) => EditKind::Typing(*idx),
+            // the offset is only written to the run once the textbox loses focus
+            Self::OffsetTextboxBlur
+            | Self::SetGameIcon(_)
+            | Self::RemoveGameIcon
+            | Self::SetSegmentIcon(..)
+            | Self::RemoveSegmentIcon(_)
+            | Self::InsertAboveClicked
+            | Self::InsertBelowClicked
+            | Self::RemoveSegmentClicked
+            | Self::MoveUpClicked
+            | Self::MoveDownClicked
+            | Self::AddComparisonClicked
+            | Self::RenameComparisonClicked
+            | Self::RemoveComparisonClicked
+            | Self::MoveComparisonUpClicked
+            | Self::MoveComparisonDownClicked
+            | Self::GenerateGoalClicked
+            | Self::SetUsesEmulator(_)
+            | Self::RemoveSpeedrunComVariable(_)
+            | Self::AddSpeedrunComVariableClicked
+            | Self::RemoveCustomVariable(_)
+            | Self::AddCustomVariableClicked
+            | Self::RemoveSegmentHistoryEntry(..) => EditKind::Discrete,
+            Self::UpdateOffsetBuffer(_)
+            | Self::SelectRow(_)
+            | Self::SplitTimeBlur(_)
+            | Self::SegmentTimeBlur(_)
+            | Self::BestSegmentTimeBlur(_)
+            | Self::SelectTimingMethod(_)
+            | Self::SelectComparison(_)
+            | Self::UpdateComparisonNameBuffer(_)
+            | Self::UpdateGoalTimeBuffer(_)
+            | Self::ComparisonTimeBlur(_)
+            | Self::UpdateNewSpeedrunComVariableName(_)
+            | Self::UpdateNewCustomVariableName(_)
+            | Self::Undo
+            | Self::Redo => EditKind::None,
+        }
+    }
 }
-// Safety: This entire impl block is spaghetti of unsafe unwraps, which needs fixed. However, none of this spaghetti
+// This entire impl block is spaghetti of unchecked unwraps, which needs fixed. However, none of this spaghetti
 // leaks into the public so until we fix it, just tread lightly when updating this.
 impl SplitsEditorState {
     pub fn new(livesplit_state: &mut LivesplitState) -> Self {
@@ -59,25 +207,49 @@ impl SplitsEditorState {
             segment_time_buffers: vec![],
             split_time_buffers: vec![],
             best_segment_time_buffers: vec![],
+
+            game_icon: None,
+            segment_icons: vec![],
+
+            selected_comparison: None,
+            comparison_time_buffers: vec![],
+            comparison_name_buffer: String::new(),
+            comparison_error: None,
+            goal_time_buffer: String::new(),
+
+            new_speedrun_com_variable_name: String::new(),
+            new_custom_variable_name: String::new(),
+
+            segment_history: None,
+
+            undo_stack: vec![],
+            redo_stack: vec![],
+            revision: 0,
+            typing_in: None,
         };
 
         me.offset_buffer = me.editor.state().offset.clone();
         me.update_buffers();
+        me.update_segment_history();
 
         me
     }
 
-    // SAFETY: this should only be called if editor_state is known to be Some
-
+    // `idx` has to be a row of `state`, the buffers are indexed by it without checking
     fn update_buffers_for_row(&mut self, idx: usize, state: &editor::State) {
         self.split_time_buffers[idx] = state.segments[idx].split_time.clone();
         // this code is not panic safe unless documented contract is followed
         self.segment_time_buffers[idx] = state.segments[idx].segment_time.clone();
         // this code is not panic safe unless documented contract is followed
         self.best_segment_time_buffers[idx] = state.segments[idx].best_segment_time.clone();
+        self.segment_icons[idx] = icon_handle(self.editor.run().segment(idx).icon());
+        self.comparison_time_buffers[idx] = self
+            .selected_comparison
+            .and_then(|comparison| state.segments[idx].comparison_times.get(comparison))
+            .cloned()
+            .unwrap_or_default();
     }
 
-    // SAFETY: this should only be called if editor_state is known to be Some
     fn update_buffers(&mut self) {
         let state = self.editor.state();
 
@@ -86,15 +258,16 @@ impl SplitsEditorState {
         self.split_time_buffers = vec![String::new(); num_rows];
         self.segment_time_buffers = vec![String::new(); num_rows];
         self.best_segment_time_buffers = vec![String::new(); num_rows];
+        self.comparison_time_buffers = vec![String::new(); num_rows];
+        self.segment_icons = vec![None; num_rows];
+        self.game_icon = icon_handle(self.editor.run().game_icon());
 
         for idx in 0..num_rows {
-            // Safety: if editor_state was none we would have thrown earlier in the function
             self.update_buffers_for_row(idx, &state);
         }
     }
-    pub fn close_window(mut self, livesplit_state: &mut LivesplitState) {
-        // todo don't auto apply
-
+    /// Replaces the timer's run with the edited one. Dropping the state instead throws the edits away.
+    pub fn apply(mut self, livesplit_state: &mut LivesplitState) {
         self.editor.parse_and_set_offset(&self.offset_buffer).ok();
 
         let run = self.editor.close();
@@ -103,8 +276,174 @@ impl SplitsEditorState {
         timer.replace_run(run, false).ok();
     }
 
+    fn selected_comparison_name(&self) -> Option<String> {
+        self.editor_state
+            .comparison_names
+            .get(self.selected_comparison?)
+            .cloned()
+    }
+
+    // Variables are addressed by their position in the lists the window shows, which only change through us.
+    fn speedrun_com_variable_name(&self, idx: usize) -> Option<String> {
+        self.editor_state
+            .metadata
+            .speedrun_com_variables()
+            .nth(idx)
+            .map(|(name, _)| name.to_owned())
+    }
+
+    fn custom_variable_name(&self, idx: usize) -> Option<String> {
+        self.editor_state
+            .metadata
+            .custom_variables()
+            .filter(|(_, variable)| variable.is_permanent)
+            .nth(idx)
+            .map(|(name, _)| name.to_owned())
+    }
+
+    fn update_segment_history(&mut self) {
+        let active = self
+            .editor_state
+            .segments
+            .iter()
+            .position(|segment| matches!(segment.selected, SelectionState::Active));
+
+        self.segment_history = active.map(|idx| {
+            SegmentHistoryView::new(self.editor.run(), idx, self.editor.selected_timing_method())
+        });
+    }
+
+    pub const fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub const fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Snapshots the run before `message` is applied, along with the field it types into. Nothing is taken for
+    /// messages that don't edit the run or keep typing into the same field.
+    fn undo_snapshot(&mut self, message: &Message) -> Option<(Run, Option<TypingField>)> {
+        let typing_in = match message.edit_kind() {
+            EditKind::None => {
+                self.typing_in = None;
+                return None;
+            }
+            EditKind::Discrete => {
+                self.typing_in = None;
+                None
+            }
+            EditKind::Typing(idx) => {
+                let field = Some((mem::discriminant(message), idx));
+                if self.typing_in == field {
+                    return None;
+                }
+                field
+            }
+        };
+
+        Some((self.editor.run().clone(), typing_in))
+    }
+
+    /// Turns the snapshot into an undo step, but only if the message actually changed the run. Failed or no-op
+    /// edits leave the undo history and the revision alone.
+    fn record_undo_step(&mut self, snapshot: Option<(Run, Option<TypingField>)>) {
+        let Some((run, typing_in)) = snapshot else {
+            return;
+        };
+        if *self.editor.run() == run {
+            return;
+        }
+
+        self.typing_in = typing_in;
+        self.push_undo_step(run);
+    }
+
+    fn push_undo_step(&mut self, run: Run) {
+        if self.undo_stack.len() == MAX_UNDO_STEPS {
+            self.undo_stack.remove(0);
+        }
+        self.undo_stack.push(run);
+        self.redo_stack.clear();
+        self.revision += 1;
+    }
+
+    pub const fn revision(&self) -> usize {
+        self.revision
+    }
+
+    /// Removes the segment times picked in the Sum of Best Cleaner, as a single undo step.
+    pub fn clean_sum_of_best(&mut self, cleaner: SumOfBestCleanerState) {
+        self.typing_in = None;
+        self.push_undo_step(self.editor.run().clone());
+
+        cleaner.apply(&mut self.editor);
+
+        self.update_buffers();
+        self.editor_state = self.editor.state();
+        self.update_segment_history();
+    }
+
+    /// Edits the segment at `idx`. The editor only edits the active segment, so it's selected for the edit and the
+    /// previous selection put back afterwards.
+    fn edit_segment(&mut self, idx: usize, edit: impl FnOnce(SegmentRow<'_>)) {
+        let mut active = idx;
+        let mut selected = vec![];
+        for (i, segment) in self.editor_state.segments.iter().enumerate() {
+            match segment.selected {
+                SelectionState::Active => active = i,
+                SelectionState::Selected => selected.push(i),
+                SelectionState::NotSelected => {}
+            }
+        }
+
+        self.editor.select_only(idx);
+        edit(self.editor.active_segment());
+
+        self.editor.select_only(active);
+        for i in selected {
+            self.editor.select_additionally(i);
+        }
+        // the last segment selected becomes the active one
+        self.editor.select_additionally(active);
+    }
+
+    /// Swaps the run being edited for `run`, keeping the selection where possible, and returns the old run.
+    fn restore(&mut self, run: Run) -> Run {
+        let active = self
+            .editor_state
+            .segments
+            .iter()
+            .position(|segment| matches!(segment.selected, SelectionState::Active));
+
+        let mut editor = RunEditor::new(run).expect(
+            "Undo snapshots come from the editor, which never holds a run without segments",
+        );
+        editor.select_timing_method(self.editor.selected_timing_method());
+        if let Some(active) = active
+            && active < editor.run().len()
+        {
+            editor.select_only(active);
+        }
+
+        let previous = mem::replace(&mut self.editor, editor).close();
+
+        let num_comparisons = self.editor.state().comparison_names.len();
+        if self
+            .selected_comparison
+            .is_some_and(|index| index >= num_comparisons)
+        {
+            self.selected_comparison = None;
+        }
+        self.offset_buffer = self.editor.state().offset;
+        self.update_buffers();
+
+        previous
+    }
+
     pub fn update(&mut self, message: Message) {
         println!("{message:?}");
+        let snapshot = self.undo_snapshot(&message);
         match message {
             Message::UpdateGameName(new_game_name) => {
                 self.editor.set_game_name(new_game_name);
@@ -117,6 +456,14 @@ impl SplitsEditorState {
                     self.editor.set_attempt_count(num_attempts);
                 }
             }
+            Message::SetGameIcon(icon) => {
+                self.editor.set_game_icon(icon.0);
+                self.game_icon = icon_handle(self.editor.run().game_icon());
+            }
+            Message::RemoveGameIcon => {
+                self.editor.remove_game_icon();
+                self.game_icon = None;
+            }
             Message::UpdateOffsetBuffer(s) => self.offset_buffer = s,
             Message::OffsetTextboxBlur => {
                 match self.editor.parse_and_set_offset(&self.offset_buffer) {
@@ -131,6 +478,17 @@ impl SplitsEditorState {
             Message::UpdateSegmentName(new_name) => {
                 self.editor.active_segment().set_name(new_name);
             }
+            Message::SetSegmentIcon(idx, icon) => {
+                // segments may have been removed while the icon picker was open
+                if idx < self.editor.run().len() {
+                    self.edit_segment(idx, |mut segment| segment.set_icon(icon.0));
+                    self.segment_icons[idx] = icon_handle(self.editor.run().segment(idx).icon());
+                }
+            }
+            Message::RemoveSegmentIcon(idx) => {
+                self.edit_segment(idx, |mut segment| segment.remove_icon());
+                self.segment_icons[idx] = None;
+            }
             Message::SplitTimeBlur(_idx) => {
                 self.update_buffers();
             }
@@ -182,7 +540,175 @@ impl SplitsEditorState {
                 self.editor.move_segments_down();
                 self.update_buffers();
             }
+            Message::SelectTimingMethod(method) => {
+                // every time the editor shows and edits is for the selected timing method
+                self.editor.select_timing_method(method);
+                self.update_buffers();
+            }
+            Message::SelectComparison(index) => {
+                self.selected_comparison = Some(index);
+                self.comparison_name_buffer = self.editor_state.comparison_names[index].clone();
+                self.comparison_error = None;
+                self.update_buffers();
+            }
+            Message::UpdateComparisonNameBuffer(text) => self.comparison_name_buffer = text,
+            Message::AddComparisonClicked => {
+                match self
+                    .editor
+                    .add_comparison(self.comparison_name_buffer.as_str())
+                {
+                    Ok(()) => {
+                        self.comparison_error = None;
+                        self.selected_comparison = self
+                            .editor
+                            .state()
+                            .comparison_names
+                            .iter()
+                            .position(|name| *name == self.comparison_name_buffer);
+                        self.update_buffers();
+                    }
+                    Err(e) => self.comparison_error = Some(e.to_string()),
+                }
+            }
+            Message::RenameComparisonClicked => {
+                if let Some(old) = self.selected_comparison_name() {
+                    match self
+                        .editor
+                        .rename_comparison(&old, &self.comparison_name_buffer)
+                    {
+                        Ok(()) => self.comparison_error = None,
+                        Err(e) => self.comparison_error = Some(e.to_string()),
+                    }
+                }
+            }
+            Message::RemoveComparisonClicked => {
+                if let Some(name) = self.selected_comparison_name() {
+                    self.editor.remove_comparison(&name);
+                    self.selected_comparison = None;
+                    self.comparison_name_buffer.clear();
+                    self.comparison_error = None;
+                    self.update_buffers();
+                }
+            }
+            Message::MoveComparisonUpClicked => {
+                if let Some(index) = self.selected_comparison
+                    && index > 0
+                    && self.editor.move_comparison(index, index - 1).is_ok()
+                {
+                    self.selected_comparison = Some(index - 1);
+                }
+            }
+            Message::MoveComparisonDownClicked => {
+                if let Some(index) = self.selected_comparison
+                    && self.editor.move_comparison(index, index + 1).is_ok()
+                {
+                    self.selected_comparison = Some(index + 1);
+                }
+            }
+            Message::UpdateGoalTimeBuffer(text) => self.goal_time_buffer = text,
+            Message::GenerateGoalClicked => {
+                // The goal is spread over the segments using their history, so it can't be faster than the sum of
+                // best or slower than the sum of worst segments. Anything outside of that gets capped.
+                match self
+                    .editor
+                    .parse_and_generate_goal_comparison(&self.goal_time_buffer)
+                {
+                    Ok(()) => {
+                        self.comparison_error = None;
+                        self.selected_comparison = self
+                            .editor
+                            .state()
+                            .comparison_names
+                            .iter()
+                            .position(|name| name == comparison::goal::NAME);
+                        self.comparison_name_buffer = comparison::goal::NAME.to_owned();
+                        self.update_buffers();
+                    }
+                    Err(e) => self.comparison_error = Some(e.to_string()),
+                }
+            }
+            Message::UpdateComparisonTimeBuffer(text, idx) => {
+                self.comparison_time_buffers[idx] = text;
+                if let Some(name) = self.selected_comparison_name() {
+                    self.editor
+                        .active_segment()
+                        .parse_and_set_comparison_time(&name, &self.comparison_time_buffers[idx])
+                        .ok();
+                }
+            }
+            Message::ComparisonTimeBlur(idx) => {
+                let state = self.editor.state();
+                self.update_buffers_for_row(idx, &state);
+            }
+            Message::UpdatePlatform(platform) => self.editor.set_platform_name(platform),
+            Message::UpdateRegion(region) => self.editor.set_region_name(region),
+            Message::SetUsesEmulator(uses_emulator) => {
+                self.editor.set_emulator_usage(uses_emulator)
+            }
+            Message::UpdateSpeedrunComVariable(idx, value) => {
+                if let Some(name) = self.speedrun_com_variable_name(idx) {
+                    self.editor.set_speedrun_com_variable(name, value);
+                }
+            }
+            Message::RemoveSpeedrunComVariable(idx) => {
+                if let Some(name) = self.speedrun_com_variable_name(idx) {
+                    self.editor.remove_speedrun_com_variable(&name);
+                }
+            }
+            Message::UpdateNewSpeedrunComVariableName(name) => {
+                self.new_speedrun_com_variable_name = name;
+            }
+            Message::AddSpeedrunComVariableClicked => {
+                let name = mem::take(&mut self.new_speedrun_com_variable_name);
+                if !name.is_empty() {
+                    self.editor.set_speedrun_com_variable(name, "");
+                }
+            }
+            Message::UpdateCustomVariable(idx, value) => {
+                if let Some(name) = self.custom_variable_name(idx) {
+                    self.editor.set_custom_variable(name, value);
+                }
+            }
+            Message::RemoveCustomVariable(idx) => {
+                if let Some(name) = self.custom_variable_name(idx) {
+                    self.editor.remove_custom_variable(&name);
+                }
+            }
+            Message::UpdateNewCustomVariableName(name) => self.new_custom_variable_name = name,
+            Message::AddCustomVariableClicked => {
+                let name = mem::take(&mut self.new_custom_variable_name);
+                if !name.is_empty() {
+                    self.editor.add_custom_variable(name);
+                }
+            }
+            Message::RemoveSegmentHistoryEntry(idx, attempt) => {
+                // the editor has no way to edit segment history, so edit a copy of the run and reopen it
+                let mut run = self.editor.run().clone();
+                run.segment_mut(idx).segment_history_mut().remove(attempt);
+                run.mark_as_modified();
+                self.restore(run);
+            }
+            Message::Undo => {
+                if let Some(run) = self.undo_stack.pop() {
+                    let current = self.restore(run);
+                    self.redo_stack.push(current);
+                    self.revision += 1;
+                }
+            }
+            Message::Redo => {
+                if let Some(run) = self.redo_stack.pop() {
+                    let current = self.restore(run);
+                    self.undo_stack.push(current);
+                    self.revision += 1;
+                }
+            }
         }
+        self.record_undo_step(snapshot);
         self.editor_state = self.editor.state();
+        self.update_segment_history();
     }
 }
+
+fn icon_handle(icon: &Image) -> Option<image::Handle> {
+    (!icon.is_empty()).then(|| image::Handle::from_bytes(icon.data().to_vec()))
+}
@@ -0,0 +1,62 @@
+use livesplit_core::{RunEditor, run::editor::cleaning::CleanUp};
+
+/// A segment time the Sum of Best Cleaner thinks might be wrong.
+pub struct CleanUpEntry {
+    pub explanation: String,
+    pub remove: bool,
+    clean_up: CleanUp,
+}
+
+pub struct SumOfBestCleanerState {
+    pub entries: Vec<CleanUpEntry>,
+    /// The splits editor revision the entries were found in. They refer to segments by position, so they're only
+    /// valid as long as the run hasn't changed since.
+    pub revision: usize,
+}
+
+#[derive(Clone, Debug)]
+pub enum Message {
+    SetRemove(usize, bool),
+    RemoveAll,
+    KeepAll,
+}
+
+impl Message {
+    pub fn into_app_message(self) -> crate::Message {
+        crate::Message::SumOfBestCleanerMessage(self)
+    }
+}
+
+impl SumOfBestCleanerState {
+    pub fn new(editor: &mut RunEditor, revision: usize) -> Self {
+        // Finding the clean ups doesn't touch the run, so collect them all up front and let the user go through
+        // them in any order instead of answering one question at a time.
+        let mut cleaner = editor.clean_sum_of_best();
+        let mut entries = vec![];
+        while let Some(potential) = cleaner.next_potential_clean_up() {
+            entries.push(CleanUpEntry {
+                explanation: potential.to_string(),
+                remove: false,
+                clean_up: potential.into(),
+            });
+        }
+
+        Self { entries, revision }
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::SetRemove(idx, remove) => self.entries[idx].remove = remove,
+            Message::RemoveAll => self.entries.iter_mut().for_each(|e| e.remove = true),
+            Message::KeepAll => self.entries.iter_mut().for_each(|e| e.remove = false),
+        }
+    }
+
+    /// Removes the segment times the user agreed to remove.
+    pub fn apply(self, editor: &mut RunEditor) {
+        let mut cleaner = editor.clean_sum_of_best();
+        for entry in self.entries.into_iter().filter(|e| e.remove) {
+            cleaner.apply(entry.clean_up);
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use iced::{
+    Length, Padding,
+    alignment::{Horizontal, Vertical},
+};
+use iced_widget::{button, checkbox, column, container, pick_list, row, scrollable, text};
+use livesplit_core::{
+    AtomicDateTime, TimeSpan,
+    timing::formatter::{Regular, TimeFormatter},
+};
+
+use crate::{
+    App,
+    state::attempt_history::{AttemptFilter, Message, SortColumn, is_finished},
+    ui::edit_splits_window::{even_row_style, odd_row_style},
+};
+
+/// The sortable columns with their labels and share of the row's width.
+const COLUMNS: [(SortColumn, &str, u16); 6] = [
+    (SortColumn::Index, "#", 1),
+    (SortColumn::Started, "Started", 3),
+    (SortColumn::Ended, "Ended", 3),
+    (SortColumn::RealTime, "Real Time", 2),
+    (SortColumn::GameTime, "Game Time", 2),
+    (SortColumn::PauseTime, "Pause Time", 2),
+];
+
+pub fn view(app: &App) -> iced::Element<'_, crate::Message> {
+    let state = app
+        .attempt_history_state
+        .as_ref()
+        .expect("Tried to draw attempt history window with no attempt history");
+
+    // the splits editor would put deleted attempts back when it's applied
+    let can_edit = app.splits_editor_state.is_none();
+
+    let toolbar = row![
+        pick_list(AttemptFilter::ALL, Some(state.filter), |filter| {
+            Message::SetFilter(filter).into_app_message()
+        }),
+        text(format!(
+            "{} of {} attempts shown, {} selected",
+            state.visible.len(),
+            state.attempts.len(),
+            state.selected.len()
+        )),
+        iced::widget::horizontal_space(),
+        button("Select All").on_press(Message::SelectAllVisible.into_app_message()),
+        button("Select None").on_press(Message::SelectNone.into_app_message()),
+        button("Delete Selected").on_press_maybe(
+            (can_edit && !state.selected.is_empty()).then_some(crate::Message::TryDeleteAttempts)
+        ),
+        button("Clear History").on_press_maybe(
+            (can_edit && !state.attempts.is_empty())
+                .then_some(crate::Message::TryClearAttemptHistory)
+        ),
+    ]
+    .spacing(8.)
+    .align_y(Vertical::Center);
+
+    let mut header = row![container(text("")).width(24.)]
+        .spacing(8.)
+        .padding(Padding::default().left(4.).right(4.));
+    for (column, label, portion) in COLUMNS {
+        let arrow = match (state.sort_column == column, state.ascending) {
+            (true, true) => " ▲",
+            (true, false) => " ▼",
+            (false, _) => "",
+        };
+        header = header.push(
+            button(text(format!("{label}{arrow}")).align_x(Horizontal::Center))
+                .on_press(Message::SortBy(column).into_app_message())
+                .width(Length::FillPortion(portion)),
+        );
+    }
+    header = header.push(text("Finished").width(Length::FillPortion(1)));
+
+    let rows = column(state.visible.iter().enumerate().map(|(row_index, &i)| {
+        let attempt = &state.attempts[i];
+        let index = attempt.index();
+        let time = attempt.time();
+
+        let cells = [
+            index.to_string(),
+            format_date(attempt.started()),
+            format_date(attempt.ended()),
+            format_time(time.real_time),
+            format_time(time.game_time),
+            format_time(attempt.pause_time()),
+        ];
+
+        let mut cells_row = row![
+            checkbox("", state.selected.contains(&index))
+                .on_toggle(
+                    move |selected| Message::ToggleSelected(index, selected).into_app_message()
+                )
+                .width(24.)
+        ]
+        .spacing(8.)
+        .padding(4.);
+        for ((_, _, portion), cell) in COLUMNS.iter().zip(cells) {
+            cells_row = cells_row.push(text(cell).width(Length::FillPortion(*portion)));
+        }
+        cells_row = cells_row.push(
+            text(if is_finished(attempt) { "Yes" } else { "No" }).width(Length::FillPortion(1)),
+        );
+
+        container(cells_row)
+            .style(if row_index.is_multiple_of(2) {
+                even_row_style
+            } else {
+                odd_row_style
+            })
+            .into()
+    }));
+
+    let mut content = column![toolbar];
+    if !can_edit {
+        content = content.push(text(
+            "Close Edit Splits to delete attempts or clear the history.",
+        ));
+    }
+
+    content
+        .push(header)
+        .push(scrollable(rows).height(Length::Fill))
+        .spacing(8.)
+        .padding(Padding::new(8.))
+        .into()
+}
+
+fn format_time(time: Option<TimeSpan>) -> String {
+    Regular::new().format(time).to_string()
+}
+
+fn format_date(date: Option<AtomicDateTime>) -> String {
+    let Some(date) = date else {
+        return "-".to_owned();
+    };
+    let time = date.time;
+
+    format!(
+        "{}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        time.year(),
+        time.month() as u8,
+        time.day(),
+        time.hour(),
+        time.minute(),
+        time.second()
+    )
+}
@@ -1,7 +1,17 @@
-use iced::{Border, Length, Padding, alignment::Horizontal};
+use iced::{
+    Border, Length, Padding,
+    alignment::{Horizontal, Vertical},
+};
 use iced_aw::{grid, grid_row};
-use iced_widget::{button, column, container, mouse_area, row, scrollable, text, text_input};
-use livesplit_core::run::editor::SegmentState;
+use iced_widget::{
+    button, checkbox, column, container, horizontal_space, image, mouse_area, radio, row,
+    scrollable, text, text_input, tooltip,
+};
+use livesplit_core::{
+    TimingMethod,
+    run::editor::SegmentState,
+    timing::formatter::{Regular, TimeFormatter},
+};
 
 trait BoolAsSome {
     fn as_some<T>(&self, t: T) -> Option<T>;
@@ -46,18 +56,62 @@ pub fn view(app: &App) -> iced::Element<'_, crate::Message> {
     )
     .on_input(|x| Message::UpdateNumAttempts(x).into_app_message());
 
-    let game_info = grid![
-        grid_row![text("Game"), text("Category")],
-        grid_row![game_tb, category_tb],
-        grid_row![text("Start Timer At"), text("Attempts")],
-        grid_row![start_timer_at_tb, attempts_tb]
+    let game_icon = column![
+        container(icon_preview(&splits_editor_state.game_icon, 64.))
+            .center(72.)
+            .style(container::bordered_box),
+        row![
+            button("Set").on_press(crate::Message::TryChooseGameIcon),
+            button("Remove").on_press_maybe(
+                splits_editor_state
+                    .game_icon
+                    .is_some()
+                    .as_some(Message::RemoveGameIcon.into_app_message())
+            ),
+        ]
+        .spacing(4.),
+    ]
+    .spacing(4.)
+    .align_x(Horizontal::Center);
+
+    let timing_method = Some(splits_editor_state.editor.selected_timing_method());
+    let timing_method_select = column![
+        text("Timing Method"),
+        radio(
+            "Real Time",
+            TimingMethod::RealTime,
+            timing_method,
+            |method| Message::SelectTimingMethod(method).into_app_message()
+        ),
+        radio(
+            "Game Time",
+            TimingMethod::GameTime,
+            timing_method,
+            |method| Message::SelectTimingMethod(method).into_app_message()
+        ),
     ]
+    .spacing(8.);
+
+    let game_info = row![
+        game_icon,
+        grid![
+            grid_row![text("Game"), text("Category")],
+            grid_row![game_tb, category_tb],
+            grid_row![text("Start Timer At"), text("Attempts")],
+            grid_row![start_timer_at_tb, attempts_tb]
+        ],
+        timing_method_select
+    ]
+    .spacing(8.)
     .padding(8.);
 
     let grid = {
         let column_width = Length::Fill;
 
-        let header = row![
+        let mut header = row![
+            text("Icon")
+                .width(ICON_COLUMN_WIDTH)
+                .align_x(Horizontal::Center),
             text("Segment Name")
                 .width(column_width)
                 .align_x(Horizontal::Center),
@@ -71,6 +125,13 @@ pub fn view(app: &App) -> iced::Element<'_, crate::Message> {
                 .width(column_width)
                 .align_x(Horizontal::Center),
         ];
+        if let Some(index) = splits_editor_state.selected_comparison {
+            header = header.push(
+                text(&editor_state.comparison_names[index])
+                    .width(column_width)
+                    .align_x(Horizontal::Center),
+            );
+        }
 
         container(column![
             header,
@@ -122,26 +183,294 @@ pub fn view(app: &App) -> iced::Element<'_, crate::Message> {
                 .can_move_down
                 .as_some(Message::MoveDownClicked.into_app_message())
         ),
+        button("Clean Sum of Best")
+            .width(Length::Fill)
+            .on_press(crate::Message::OpenSumOfBestCleanerWindow),
     ]
     .width(175.);
 
-    let splits_section = row![buttons, grid].spacing(8.);
+    let splits_section = row![buttons, grid, comparisons(splits_editor_state)].spacing(8.);
+
+    let dialog_buttons = row![
+        button("Undo").on_press_maybe(
+            splits_editor_state
+                .can_undo()
+                .as_some(Message::Undo.into_app_message())
+        ),
+        button("Redo").on_press_maybe(
+            splits_editor_state
+                .can_redo()
+                .as_some(Message::Redo.into_app_message())
+        ),
+        horizontal_space(),
+        button("OK").on_press(crate::Message::ConfirmSplitsEdit),
+        button("Cancel").on_press(crate::Message::CancelSplitsEdit),
+    ]
+    .spacing(8.)
+    .padding(Padding::new(8.));
+
     column![
         game_info,
-        container(splits_section).padding(Padding::new(8.))
+        metadata(splits_editor_state),
+        container(splits_section)
+            .padding(Padding::new(8.))
+            .height(Length::Fill),
+        segment_history(splits_editor_state),
+        dialog_buttons
     ]
     .spacing(8.)
     .into()
 }
-fn table_row<'a, R>(
+
+fn metadata(splits_editor_state: &SplitsEditorState) -> iced::Element<'_, crate::Message> {
+    let metadata = &splits_editor_state.editor_state.metadata;
+
+    let platform = row![
+        text("Platform"),
+        text_input("", metadata.platform_name())
+            .on_input(|x| Message::UpdatePlatform(x).into_app_message()),
+        text("Region"),
+        text_input("", metadata.region_name())
+            .on_input(|x| Message::UpdateRegion(x).into_app_message()),
+        checkbox("Uses Emulator", metadata.uses_emulator())
+            .on_toggle(|x| Message::SetUsesEmulator(x).into_app_message()),
+    ]
+    .spacing(8.)
+    .align_y(Vertical::Center);
+
+    let speedrun_com_variables = variable_list(
+        "Speedrun.com Variables",
+        metadata
+            .speedrun_com_variables()
+            .map(|(name, value)| (name, value.as_str())),
+        Message::UpdateSpeedrunComVariable,
+        Message::RemoveSpeedrunComVariable,
+        &splits_editor_state.new_speedrun_com_variable_name,
+        Message::UpdateNewSpeedrunComVariableName,
+        Message::AddSpeedrunComVariableClicked,
+    );
+
+    // temporary variables come from auto splitters and such, they aren't saved so there's no point editing them
+    let custom_variables = variable_list(
+        "Custom Variables",
+        metadata
+            .custom_variables()
+            .filter(|(_, variable)| variable.is_permanent)
+            .map(|(name, variable)| (name, variable.value.as_str())),
+        Message::UpdateCustomVariable,
+        Message::RemoveCustomVariable,
+        &splits_editor_state.new_custom_variable_name,
+        Message::UpdateNewCustomVariableName,
+        Message::AddCustomVariableClicked,
+    );
+
+    column![
+        platform,
+        row![speedrun_com_variables, custom_variables].spacing(8.)
+    ]
+    .spacing(8.)
+    .padding(Padding::default().left(8.).right(8.))
+    .into()
+}
+
+fn variable_list<'a>(
+    title: &'a str,
+    variables: impl Iterator<Item = (&'a str, &'a str)>,
+    on_update: impl Fn(usize, String) -> Message + Copy + 'a,
+    on_remove: impl Fn(usize) -> Message,
+    new_name: &'a str,
+    on_new_name: impl Fn(String) -> Message + 'a,
+    on_add: Message,
+) -> iced::Element<'a, crate::Message> {
+    let rows = column(variables.enumerate().map(|(idx, (name, value))| {
+        row![
+            text(name).width(Length::FillPortion(1)),
+            text_input("", value)
+                .on_input(move |x| on_update(idx, x).into_app_message())
+                .width(Length::FillPortion(2)),
+            button("Remove").on_press(on_remove(idx).into_app_message()),
+        ]
+        .spacing(8.)
+        .align_y(Vertical::Center)
+        .into()
+    }))
+    .spacing(4.);
+
+    column![
+        text(title),
+        container(scrollable(rows)).max_height(100.),
+        row![
+            text_input("Name", new_name).on_input(move |x| on_new_name(x).into_app_message()),
+            button("Add").on_press_maybe((!new_name.is_empty()).as_some(on_add.into_app_message())),
+        ]
+        .spacing(8.),
+    ]
+    .spacing(4.)
+    .width(Length::Fill)
+    .into()
+}
+
+fn segment_history(splits_editor_state: &SplitsEditorState) -> iced::Element<'_, crate::Message> {
+    let Some(history) = &splits_editor_state.segment_history else {
+        return column![].into();
+    };
+    let stats = &history.stats;
+    let format = |time| Regular::new().format(time).to_string();
+
+    let name = &splits_editor_state.editor_state.segments[history.segment_index].name;
+    let summary = text(format!(
+        "{name}: mean {}, median {}, std dev {}, {} golds, reset rate {}",
+        format(stats.mean),
+        format(stats.median),
+        format(stats.std_dev),
+        stats.gold_count,
+        stats
+            .reset_rate
+            .map_or_else(|| "-".to_owned(), |rate| format!("{:.1}%", rate * 100.)),
+    ));
+
+    let segment_index = history.segment_index;
+    let entries = column(
+        history
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(row, &(attempt, time))| {
+                container(
+                    row![
+                        text(format!("Attempt {attempt}")).width(Length::Fill),
+                        text(format(time)).width(Length::Fill),
+                        button("Remove").on_press(
+                            Message::RemoveSegmentHistoryEntry(segment_index, attempt)
+                                .into_app_message()
+                        ),
+                    ]
+                    .spacing(8.)
+                    .padding(2.)
+                    .align_y(Vertical::Center),
+                )
+                .style(if row.is_multiple_of(2) {
+                    even_row_style
+                } else {
+                    odd_row_style
+                })
+                .into()
+            }),
+    );
+
+    column![summary, scrollable(entries).height(150.)]
+        .spacing(4.)
+        .padding(Padding::default().left(8.).right(8.))
+        .into()
+}
+
+const ICON_COLUMN_WIDTH: f32 = 40.;
+
+fn icon_preview<'a>(icon: &Option<image::Handle>, size: f32) -> iced::Element<'a, crate::Message> {
+    match icon {
+        Some(handle) => image(handle.clone()).width(size).height(size).into(),
+        None => text("None").into(),
+    }
+}
+
+fn comparisons(splits_editor_state: &SplitsEditorState) -> iced::Element<'_, crate::Message> {
+    let selected = splits_editor_state.selected_comparison;
+
+    let list = scrollable(column(
+        splits_editor_state
+            .editor_state
+            .comparison_names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| {
+                mouse_area(container(text(name)).width(Length::Fill).padding(4.).style(
+                    if selected == Some(index) {
+                        active_row_style
+                    } else if index.is_multiple_of(2) {
+                        even_row_style
+                    } else {
+                        odd_row_style
+                    },
+                ))
+                .on_press(Message::SelectComparison(index).into_app_message())
+                .into()
+            }),
+    ))
+    .height(Length::Fill);
+
+    let can_move_down = selected
+        .is_some_and(|index| index + 1 < splits_editor_state.editor_state.comparison_names.len());
+
+    let mut panel = column![
+        text("Comparisons"),
+        list,
+        text_input(
+            "Comparison name",
+            &splits_editor_state.comparison_name_buffer
+        )
+        .on_input(|x| Message::UpdateComparisonNameBuffer(x).into_app_message()),
+    ]
+    .spacing(8.)
+    .width(200.);
+
+    if let Some(error) = &splits_editor_state.comparison_error {
+        panel = panel.push(text(error));
+    }
+
+    panel
+        .push(
+            button("Add")
+                .width(Length::Fill)
+                .on_press(Message::AddComparisonClicked.into_app_message()),
+        )
+        .push(
+            button("Rename").width(Length::Fill).on_press_maybe(
+                selected
+                    .is_some()
+                    .as_some(Message::RenameComparisonClicked.into_app_message()),
+            ),
+        )
+        .push(
+            button("Remove").width(Length::Fill).on_press_maybe(
+                selected
+                    .is_some()
+                    .as_some(Message::RemoveComparisonClicked.into_app_message()),
+            ),
+        )
+        .push(
+            button("Move Up").width(Length::Fill).on_press_maybe(
+                selected
+                    .is_some_and(|index| index > 0)
+                    .as_some(Message::MoveComparisonUpClicked.into_app_message()),
+            ),
+        )
+        .push(button("Move Down").width(Length::Fill).on_press_maybe(
+            can_move_down.as_some(Message::MoveComparisonDownClicked.into_app_message()),
+        ))
+        .push(text("Goal"))
+        .push(
+            text_input(
+                "Goal time, e.g. 1:30:00",
+                &splits_editor_state.goal_time_buffer,
+            )
+            .on_input(|x| Message::UpdateGoalTimeBuffer(x).into_app_message())
+            .on_submit(Message::GenerateGoalClicked.into_app_message()),
+        )
+        .push(
+            button("Generate Goal").width(Length::Fill).on_press_maybe(
+                (!splits_editor_state.goal_time_buffer.is_empty())
+                    .as_some(Message::GenerateGoalClicked.into_app_message()),
+            ),
+        )
+        .into()
+}
+
+fn table_row<'a>(
     index: usize,
     segment: &SegmentState,
     column_width: Length,
     splits_editor_state: &SplitsEditorState,
-) -> iced::Element<'a, crate::Message, iced::Theme, R>
-where
-    R: iced::advanced::text::Renderer + 'a,
-{
+) -> iced::Element<'a, crate::Message> {
     fn gold_style(t: &iced::Theme, s: iced::widget::text_input::Status) -> text_input::Style {
         let mut style = text_input::default(t, s);
 
@@ -200,15 +529,51 @@ where
             }
             .into_app_message()
         });
-    mouse_area(
-        container(
-            row![segment_name, split_time, segment_time, best_segment_time,]
-                .spacing(8.)
-                .padding(4.),
+    // click to pick an icon, right click to remove it
+    let icon = tooltip(
+        mouse_area(
+            container(icon_preview(&splits_editor_state.segment_icons[index], 24.))
+                .center_x(ICON_COLUMN_WIDTH)
+                .center_y(Length::Fixed(30.)),
         )
-        .style(if segment.selected.is_selected_or_active() {
+        .on_press(crate::Message::TryChooseSegmentIcon(index))
+        .on_right_press(Message::RemoveSegmentIcon(index).into_app_message()),
+        text("Click to choose an icon, right click to remove it"),
+        tooltip::Position::Bottom,
+    );
+
+    let mut cells = row![
+        icon,
+        segment_name,
+        split_time,
+        segment_time,
+        best_segment_time,
+    ]
+    .spacing(8.)
+    .padding(4.);
+
+    if splits_editor_state.selected_comparison.is_some() {
+        cells = cells.push(
+            text_input("", &splits_editor_state.comparison_time_buffers[index])
+                .on_input(move |text| {
+                    Message::UpdateComparisonTimeBuffer(text, index).into_app_message()
+                })
+                .width(column_width)
+                .wrap_focus(move |f| {
+                    if f {
+                        Message::SelectRow(index)
+                    } else {
+                        Message::ComparisonTimeBlur(index)
+                    }
+                    .into_app_message()
+                }),
+        );
+    }
+
+    mouse_area(
+        container(cells).style(if segment.selected.is_selected_or_active() {
             active_row_style
-        } else if index % 2 == 0 {
+        } else if index.is_multiple_of(2) {
             even_row_style
         } else {
             odd_row_style
@@ -218,7 +583,7 @@ where
     .into()
 }
 
-fn odd_row_style(theme: &iced::Theme) -> container::Style {
+pub fn odd_row_style(theme: &iced::Theme) -> container::Style {
     container::Style {
         text_color: Some(theme.extended_palette().background.base.text),
         background: Some(iced::Background::Color(
@@ -233,7 +598,7 @@ fn odd_row_style(theme: &iced::Theme) -> container::Style {
     }
 }
 
-fn even_row_style(theme: &iced::Theme) -> container::Style {
+pub fn even_row_style(theme: &iced::Theme) -> container::Style {
     container::Style {
         text_color: Some(theme.extended_palette().background.weak.text),
         background: Some(iced::Background::Color(
@@ -248,7 +613,7 @@ fn even_row_style(theme: &iced::Theme) -> container::Style {
     }
 }
 
-fn active_row_style(theme: &iced::Theme) -> container::Style {
+pub fn active_row_style(theme: &iced::Theme) -> container::Style {
     container::Style {
         text_color: Some(theme.extended_palette().primary.weak.text),
         background: Some(iced::Background::Color(
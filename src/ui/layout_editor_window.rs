@@ -0,0 +1,424 @@
+use std::fmt;
+
+use iced::{Border, Length, Padding};
+use iced_widget::{
+    button, checkbox, column, container, mouse_area, pick_list, row, scrollable, text, text_input,
+};
+use livesplit_core::{
+    TimingMethod,
+    component::{
+        splits::{ColumnStartWith, ColumnUpdateTrigger, ColumnUpdateWith},
+        timer::DeltaGradient,
+    },
+    layout::LayoutDirection,
+    settings::{Alignment, Color, ColumnKind, Gradient, ListGradient, Value},
+    timing::formatter::{Accuracy, DigitsFormat},
+};
+
+use crate::{
+    App,
+    state::layout_editor::{
+        ComponentKind, LayoutEditorState, Message, SettingValue, SettingsGroup, field_text,
+        gradient_colors,
+    },
+    ui::edit_splits_window::{active_row_style, even_row_style, odd_row_style},
+    widgets::FocalWrapper,
+};
+
+pub fn view(app: &App) -> iced::Element<'_, crate::Message> {
+    let layout_editor_state = app
+        .layout_editor_state
+        .as_ref()
+        .expect("Tried to draw layout editor window with no layout editor");
+
+    let editor_state = &layout_editor_state.editor_state;
+
+    let components = column(
+        editor_state
+            .components
+            .iter()
+            .enumerate()
+            .map(|(idx, name)| {
+                mouse_area(
+                    container(text(name.clone()))
+                        .width(Length::Fill)
+                        .padding(4.)
+                        .style(if idx == editor_state.selected_component as usize {
+                            active_row_style
+                        } else if idx.is_multiple_of(2) {
+                            even_row_style
+                        } else {
+                            odd_row_style
+                        }),
+                )
+                .on_press(Message::SelectComponent(idx).into_app_message())
+                .into()
+            }),
+    );
+
+    let buttons = column![
+        row![
+            pick_list(
+                ComponentKind::ALL,
+                layout_editor_state.component_to_add,
+                |kind| Message::ComponentToAddSelected(kind).into_app_message()
+            )
+            .placeholder("Component")
+            .width(Length::Fill),
+            button("Add").on_press_maybe(
+                layout_editor_state
+                    .component_to_add
+                    .map(|_| Message::AddComponentClicked.into_app_message())
+            ),
+        ]
+        .spacing(8.),
+        button("Remove Component")
+            .width(Length::Fill)
+            .on_press_maybe(
+                editor_state
+                    .buttons
+                    .can_remove
+                    .then_some(Message::RemoveComponentClicked.into_app_message())
+            ),
+        button("Duplicate Component")
+            .width(Length::Fill)
+            .on_press(Message::DuplicateComponentClicked.into_app_message()),
+        button("Move Up").width(Length::Fill).on_press_maybe(
+            editor_state
+                .buttons
+                .can_move_up
+                .then_some(Message::MoveUpClicked.into_app_message())
+        ),
+        button("Move Down").width(Length::Fill).on_press_maybe(
+            editor_state
+                .buttons
+                .can_move_down
+                .then_some(Message::MoveDownClicked.into_app_message())
+        ),
+    ]
+    .spacing(4.);
+
+    let component_list = column![
+        container(scrollable(components).height(Length::Fill)).style(bordered),
+        buttons
+    ]
+    .spacing(8.)
+    .width(250.);
+
+    let tab_button = |label, tab| {
+        button(label).width(Length::Fill).on_press_maybe(
+            (layout_editor_state.settings_tab != tab)
+                .then_some(Message::SelectSettingsTab(tab).into_app_message()),
+        )
+    };
+
+    let group = layout_editor_state.settings_tab;
+    let fields = column(
+        layout_editor_state
+            .description(group)
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(idx, field)| {
+                row![
+                    text(field.text.clone()).width(Length::FillPortion(2)),
+                    container(field_editor(layout_editor_state, group, idx, &field.value))
+                        .width(Length::FillPortion(3)),
+                ]
+                .spacing(8.)
+                .into()
+            }),
+    )
+    .spacing(4.)
+    .padding(Padding::new(8.));
+
+    let settings = column![
+        row![
+            tab_button("Component Settings", SettingsGroup::Component),
+            tab_button("Layout Settings", SettingsGroup::General),
+        ]
+        .spacing(8.),
+        container(scrollable(fields).height(Length::Fill)).style(bordered),
+        row![
+            button("Apply").on_press(crate::Message::ApplyLayout),
+            button("Cancel").on_press(crate::Message::CancelLayoutEdits),
+        ]
+        .spacing(8.),
+    ]
+    .spacing(8.);
+
+    container(row![component_list, settings].spacing(8.))
+        .padding(Padding::new(8.))
+        .into()
+}
+
+fn field_editor<'a>(
+    state: &LayoutEditorState,
+    group: SettingsGroup,
+    idx: usize,
+    value: &Value,
+) -> iced::Element<'a, crate::Message> {
+    let set = move |value| Message::SetValue(group, idx, SettingValue(value)).into_app_message();
+
+    if let Some(options) = choices(value) {
+        let selected = options.iter().find(|c| c.value == *value).cloned();
+        let kind_picker =
+            pick_list(options, selected, move |c: Choice| set(c.value)).width(Length::Fill);
+
+        // gradients pick their kind from the list, and then need their colors typed in
+        let colors = match value {
+            Value::Gradient(g)
+            | Value::ListGradient(ListGradient::Same(g))
+            | Value::DeltaGradient(DeltaGradient::Gradient(g)) => {
+                gradient_colors(*g).iter().filter(|c| c.is_some()).count()
+            }
+            Value::ListGradient(ListGradient::Alternating(..)) => 2,
+            _ => 0,
+        };
+
+        return (0..colors)
+            .fold(row![kind_picker].spacing(4.), |row, slot| {
+                row.push(buffered_input(state, group, idx, slot, value))
+            })
+            .into();
+    }
+
+    match value {
+        Value::Bool(b) => checkbox("", *b)
+            .on_toggle(move |b| set(Value::Bool(b)))
+            .into(),
+        Value::String(s) => text_input("", s)
+            .on_input(move |s| set(Value::String(s)))
+            .into(),
+        Value::OptionalString(s) => text_input("", s.as_deref().unwrap_or_default())
+            .on_input(move |s| set(Value::OptionalString((!s.is_empty()).then_some(s))))
+            .into(),
+        Value::UInt(_)
+        | Value::Int(_)
+        | Value::Color(_)
+        | Value::OptionalColor(_)
+        | Value::Font(_) => buffered_input(state, group, idx, 0, value),
+        Value::Hotkey(hotkey) => text(hotkey.map(|h| h.to_string()).unwrap_or_default()).into(),
+        _ => text("Not editable").into(),
+    }
+}
+
+fn buffered_input<'a>(
+    state: &LayoutEditorState,
+    group: SettingsGroup,
+    idx: usize,
+    slot: usize,
+    value: &Value,
+) -> iced::Element<'a, crate::Message> {
+    let content = state
+        .field_buffers
+        .get(&(group, idx, slot))
+        .cloned()
+        .unwrap_or_else(|| field_text(value, slot));
+
+    let placeholder = match value {
+        Value::OptionalColor(_) => "Default",
+        Value::Font(_) => "Default Font",
+        Value::UInt(_) | Value::Int(_) => "",
+        _ => "#RRGGBBAA",
+    };
+
+    text_input(placeholder, &content)
+        .on_input(move |s| Message::UpdateFieldBuffer(group, idx, slot, s).into_app_message())
+        .wrap_focus(move |f| {
+            if f {
+                crate::Message::None
+            } else {
+                Message::FieldBlur(group, idx, slot).into_app_message()
+            }
+        })
+        .into()
+}
+
+/// An entry in a setting's pick list.
+#[derive(Clone)]
+struct Choice {
+    label: &'static str,
+    value: Value,
+}
+
+impl PartialEq for Choice {
+    fn eq(&self, other: &Self) -> bool {
+        self.label == other.label
+    }
+}
+
+impl fmt::Display for Choice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label)
+    }
+}
+
+/// The options for settings that are picked from a list, or `None` if the setting is edited some other way.
+/// Gradient options carry over the current colors so switching between kinds doesn't lose them.
+fn choices(value: &Value) -> Option<Vec<Choice>> {
+    fn options<T>(options: &[(&'static str, T)], f: impl Fn(T) -> Value) -> Vec<Choice>
+    where
+        T: Copy,
+    {
+        options
+            .iter()
+            .map(|&(label, v)| Choice { label, value: f(v) })
+            .collect()
+    }
+
+    fn colors_or_default(gradient: Option<Gradient>) -> (Color, Color) {
+        let [a, b] = gradient.map(gradient_colors).unwrap_or_default();
+        (
+            a.unwrap_or(Color::rgba8(255, 255, 255, 255)),
+            b.unwrap_or(Color::rgba8(0, 0, 0, 255)),
+        )
+    }
+
+    fn gradients(gradient: Option<Gradient>) -> [(&'static str, Gradient); 4] {
+        let (a, b) = colors_or_default(gradient);
+        [
+            ("Transparent", Gradient::Transparent),
+            ("Plain", Gradient::Plain(a)),
+            ("Vertical", Gradient::Vertical(a, b)),
+            ("Horizontal", Gradient::Horizontal(a, b)),
+        ]
+    }
+
+    Some(match value {
+        Value::Accuracy(_) => options(
+            &[
+                ("Seconds", Accuracy::Seconds),
+                ("Tenths", Accuracy::Tenths),
+                ("Hundredths", Accuracy::Hundredths),
+                ("Milliseconds", Accuracy::Milliseconds),
+            ],
+            Value::Accuracy,
+        ),
+        Value::DigitsFormat(_) => options(
+            &[
+                ("1", DigitsFormat::SingleDigitSeconds),
+                ("01", DigitsFormat::DoubleDigitSeconds),
+                ("0:01", DigitsFormat::SingleDigitMinutes),
+                ("00:01", DigitsFormat::DoubleDigitMinutes),
+                ("0:00:01", DigitsFormat::SingleDigitHours),
+                ("00:00:01", DigitsFormat::DoubleDigitHours),
+            ],
+            Value::DigitsFormat,
+        ),
+        Value::OptionalTimingMethod(_) => options(
+            &[
+                ("Current Timing Method", None),
+                ("Real Time", Some(TimingMethod::RealTime)),
+                ("Game Time", Some(TimingMethod::GameTime)),
+            ],
+            Value::OptionalTimingMethod,
+        ),
+        Value::Alignment(_) => options(
+            &[
+                ("Automatic", Alignment::Auto),
+                ("Left", Alignment::Left),
+                ("Center", Alignment::Center),
+            ],
+            Value::Alignment,
+        ),
+        Value::ColumnKind(_) => options(
+            &[
+                ("Time", ColumnKind::Time),
+                ("Variable", ColumnKind::Variable),
+            ],
+            Value::ColumnKind,
+        ),
+        Value::ColumnStartWith(_) => options(
+            &[
+                ("Empty", ColumnStartWith::Empty),
+                ("Comparison Time", ColumnStartWith::ComparisonTime),
+                (
+                    "Comparison Segment Time",
+                    ColumnStartWith::ComparisonSegmentTime,
+                ),
+                ("Possible Time Save", ColumnStartWith::PossibleTimeSave),
+            ],
+            Value::ColumnStartWith,
+        ),
+        Value::ColumnUpdateWith(_) => options(
+            &[
+                ("Don't Update", ColumnUpdateWith::DontUpdate),
+                ("Split Time", ColumnUpdateWith::SplitTime),
+                ("Delta", ColumnUpdateWith::Delta),
+                ("Delta or Split Time", ColumnUpdateWith::DeltaWithFallback),
+                ("Segment Time", ColumnUpdateWith::SegmentTime),
+                ("Segment Delta", ColumnUpdateWith::SegmentDelta),
+                (
+                    "Segment Delta or Segment Time",
+                    ColumnUpdateWith::SegmentDeltaWithFallback,
+                ),
+            ],
+            Value::ColumnUpdateWith,
+        ),
+        Value::ColumnUpdateTrigger(_) => options(
+            &[
+                (
+                    "On Starting Segment",
+                    ColumnUpdateTrigger::OnStartingSegment,
+                ),
+                ("Contextual", ColumnUpdateTrigger::Contextual),
+                ("On Ending Segment", ColumnUpdateTrigger::OnEndingSegment),
+            ],
+            Value::ColumnUpdateTrigger,
+        ),
+        Value::LayoutDirection(_) => options(
+            &[
+                ("Vertical", LayoutDirection::Vertical),
+                ("Horizontal", LayoutDirection::Horizontal),
+            ],
+            Value::LayoutDirection,
+        ),
+        Value::Gradient(g) => options(&gradients(Some(*g)), Value::Gradient),
+        Value::ListGradient(lg) => {
+            let (current, alternating) = match *lg {
+                ListGradient::Same(g) => (Some(g), None),
+                ListGradient::Alternating(a, b) => (Some(Gradient::Vertical(a, b)), Some((a, b))),
+            };
+            let mut list = options(&gradients(current), |g| {
+                Value::ListGradient(ListGradient::Same(g))
+            });
+            let (a, b) = alternating.unwrap_or_else(|| colors_or_default(current));
+            list.push(Choice {
+                label: "Alternating",
+                value: Value::ListGradient(ListGradient::Alternating(a, b)),
+            });
+            list
+        }
+        Value::DeltaGradient(dg) => {
+            let current = match *dg {
+                DeltaGradient::Gradient(g) => Some(g),
+                _ => None,
+            };
+            let mut list = options(&gradients(current), |g| {
+                Value::DeltaGradient(DeltaGradient::Gradient(g))
+            });
+            list.extend(options(
+                &[
+                    ("Delta Plain", DeltaGradient::DeltaPlain),
+                    ("Delta Vertical", DeltaGradient::DeltaVertical),
+                    ("Delta Horizontal", DeltaGradient::DeltaHorizontal),
+                ],
+                Value::DeltaGradient,
+            ));
+            list
+        }
+        _ => return None,
+    })
+}
+
+fn bordered(theme: &iced::Theme) -> container::Style {
+    container::Style {
+        border: Border {
+            color: theme.extended_palette().background.base.text,
+            width: 1.,
+            radius: Default::default(),
+        },
+        ..Default::default()
+    }
+}
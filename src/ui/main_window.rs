@@ -1,11 +1,18 @@
 use iced::{
     Border, Length, Theme,
     border::Radius,
-    widget::{column, container, image},
+    widget::{Column, column, container, horizontal_rule, image, mouse_area, row, text},
 };
 
 use crate::{App, Message};
 
+/// The recent files submenu that's open in the context menu.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecentMenu {
+    Splits,
+    Layouts,
+}
+
 pub fn view(app: &App) -> iced::Element<'_, Message> {
     let (width, height) = app.livesplit_state.last_rendered_size();
     // TODO figure out how to avoid this clone
@@ -17,6 +24,12 @@ pub fn view(app: &App) -> iced::Element<'_, Message> {
 
     let container = container(im);
 
+    let game_time_label = if app.livesplit_state.is_game_time_paused() {
+        "Resume Game Time"
+    } else {
+        "Pause Game Time"
+    };
+
     iced_aw::ContextMenu::new(container, || {
         let style = |t: &Theme, _| iced::widget::button::Style {
             background: Some(iced::Background::Color(t.palette().background)),
@@ -29,30 +42,109 @@ pub fn view(app: &App) -> iced::Element<'_, Message> {
             ..Default::default()
         };
 
-        column![
-            iced::widget::button("Load Splits")
-                .on_press(Message::TryLoadSplits)
-                .style(style)
-                .width(Length::Fill),
-            iced::widget::button("Save Splits")
-                .on_press(Message::TrySaveSplits)
-                .style(style)
-                .width(Length::Fill),
-            iced::widget::button("EditSplits")
-                .on_press(Message::OpenEditSplitsWindow)
-                .style(style)
-                .width(Length::Fill),
-            iced::widget::button("Load Layout")
-                .on_press(Message::TryLoadLayout)
-                .style(style)
-                .width(Length::Fill),
-            iced::widget::button("Settings")
-                .on_press(Message::OpenSettingsWindow)
+        let menu_button = |label, message| {
+            // the context menu closes on click, so submenus open on hover instead
+            mouse_area(
+                iced::widget::button(label)
+                    .on_press(message)
+                    .style(style)
+                    .width(Length::Fill),
+            )
+            .on_enter(Message::ShowRecentMenu(None))
+        };
+
+        let submenu_button = |label, recent_menu| {
+            let label = if app.recent_menu == Some(recent_menu) {
+                format!("{label} <")
+            } else {
+                format!("{label} >")
+            };
+            mouse_area(
+                iced::widget::button(text(label))
+                    .style(style)
+                    .width(Length::Fill),
+            )
+            .on_enter(Message::ShowRecentMenu(Some(recent_menu)))
+        };
+
+        let file_name = |path: &std::path::Path| {
+            path.file_name()
+                .unwrap_or(path.as_os_str())
+                .to_string_lossy()
+                .into_owned()
+        };
+
+        let entry = |label: String, message| {
+            iced::widget::button(text(label))
+                .on_press(message)
                 .style(style)
                 .width(Length::Fill)
+                .into()
+        };
+
+        let entries: Vec<iced::Element<'_, Message>> = match app.recent_menu {
+            Some(RecentMenu::Splits) => app
+                .settings
+                .recent_splits
+                .iter()
+                .map(|recent| {
+                    let label = match &recent.layout {
+                        Some(layout) => {
+                            format!("{} ({})", file_name(&recent.path), file_name(layout))
+                        }
+                        None => file_name(&recent.path),
+                    };
+                    entry(label, Message::TryLoadRecentSplits(recent.path.clone()))
+                })
+                .collect(),
+            Some(RecentMenu::Layouts) => app
+                .settings
+                .recent_layouts
+                .iter()
+                .map(|path| entry(file_name(path), Message::TryLoadRecentLayout(path.clone())))
+                .collect(),
+            None => vec![],
+        };
+
+        let submenu = match app.recent_menu {
+            Some(_) if entries.is_empty() => column![
+                iced::widget::button("No recent files")
+                    .style(style)
+                    .width(Length::Fill)
+            ],
+            Some(_) => Column::with_children(entries),
+            None => column![],
+        };
+
+        let menu = column![
+            menu_button("Start/Split", Message::StartOrSplit),
+            menu_button("Reset", Message::Reset),
+            menu_button("Undo Split", Message::UndoSplit),
+            menu_button("Skip Split", Message::SkipSplit),
+            menu_button("Pause", Message::TogglePause),
+            menu_button("Undo All Pauses", Message::UndoAllPauses),
+            menu_button("Previous Comparison", Message::PreviousComparison),
+            menu_button("Next Comparison", Message::NextComparison),
+            menu_button("Toggle Timing Method", Message::ToggleTimingMethod),
+            menu_button(game_time_label, Message::ToggleGameTimePause),
+            horizontal_rule(1),
+            menu_button("Load Splits", Message::TryLoadSplits),
+            submenu_button("Recent Splits", RecentMenu::Splits),
+            menu_button("Save Splits", Message::TrySaveSplits),
+            menu_button("Edit Splits", Message::OpenEditSplitsWindow),
+            menu_button("Attempt History", Message::OpenAttemptHistoryWindow),
+            menu_button("Load Layout", Message::TryLoadLayout),
+            submenu_button("Recent Layouts", RecentMenu::Layouts),
+            menu_button("Save Layout", Message::TrySaveLayout),
+            menu_button("Save Layout As", Message::TrySaveLayoutAs),
+            menu_button("Edit Layout", Message::OpenLayoutEditorWindow),
+            menu_button("Settings", Message::OpenSettingsWindow),
         ]
-        .width(150.)
-        .into()
+        .width(180.);
+
+        let submenu_width = if app.recent_menu.is_some() { 240. } else { 0. };
+
+        row![menu, submenu.width(submenu_width)].into()
     })
     .into()
 }
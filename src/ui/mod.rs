@@ -1,3 +1,6 @@
+pub mod attempt_history_window;
 pub mod edit_splits_window;
+pub mod layout_editor_window;
 pub mod main_window;
 pub mod settings_window;
+pub mod sum_of_best_cleaner_window;
@@ -4,12 +4,26 @@ use iced::{Border, Length, Padding};
 use iced_aw::{grid, grid_row};
 use iced_widget::container;
 
-use crate::{App, Message, widgets::FocalWrapper};
+use crate::{
+    App, Message, app_settings::CompareAgainst, auto_splitter::AutoSplitterStatus,
+    livesplit_state::HotkeyMode, widgets::FocalWrapper,
+};
 
 pub fn view(app: &App) -> iced::Element<'_, Message> {
+    let hotkey_mode = match app.livesplit_state.hotkey_mode() {
+        HotkeyMode::Global(..) => "Mode: Global".to_owned(),
+        HotkeyMode::WindowLocal { reason, .. } => format!(
+            "Mode: Window-local (only while the timer window is focused). Global hotkeys are unavailable: {reason}"
+        ),
+        HotkeyMode::Disabled(_) => {
+            "Mode: Disabled from the command line with --no-hotkeys".to_owned()
+        }
+    };
+
     let hotkeys = container(
         column![
             iced::widget::text("Hotkeys: "),
+            iced::widget::text(hotkey_mode),
             container(
                 grid(
                     app.hotkeys
@@ -45,16 +59,274 @@ pub fn view(app: &App) -> iced::Element<'_, Message> {
         ]
         .spacing(8.),
     )
-    .style(|theme: &iced::Theme| container::Style {
+    .style(section_style)
+    .padding(Padding::default().left(16.0).top(8.).bottom(8.));
+
+    iced::widget::scrollable(
+        container(
+            column![
+                hotkeys,
+                timing(app),
+                auto_splitter(app),
+                server(app),
+                websocket_server(app),
+                auto_save(app),
+                backups(app)
+            ]
+            .spacing(16.),
+        )
+        .padding(Padding::new(16.0)),
+    )
+    .into()
+}
+
+fn timing(app: &App) -> iced::Element<'_, Message> {
+    // read from the timer rather than the settings, the hotkey can switch it behind our back
+    let current = Some(CompareAgainst::from(
+        app.livesplit_state.current_timing_method(),
+    ));
+
+    container(
+        column![
+            iced::widget::text("Compare Against: "),
+            row![
+                iced::widget::radio(
+                    "Real Time",
+                    CompareAgainst::RealTime,
+                    current,
+                    Message::SetCompareAgainst
+                ),
+                iced::widget::radio(
+                    "Game Time",
+                    CompareAgainst::GameTime,
+                    current,
+                    Message::SetCompareAgainst
+                ),
+            ]
+            .spacing(16.),
+        ]
+        .spacing(8.),
+    )
+    .style(section_style)
+    .padding(Padding::default().left(16.0).top(8.).bottom(8.))
+    .width(Length::Fill)
+    .into()
+}
+
+fn auto_splitter(app: &App) -> iced::Element<'_, Message> {
+    let auto_splitter = &app.livesplit_state.auto_splitter;
+
+    let status = match auto_splitter.status() {
+        AutoSplitterStatus::NotLoaded => "No auto splitter loaded".to_owned(),
+        AutoSplitterStatus::Loading(path) => format!("Loading {}...", path.display()),
+        AutoSplitterStatus::Running(path) => format!("Running {}", path.display()),
+        AutoSplitterStatus::Failed(e) => format!("Auto splitter failed: {e}"),
+    };
+
+    let mut content = column![
+        iced::widget::text("Auto Splitter: "),
+        iced::widget::text(status),
+    ]
+    .spacing(8.);
+
+    if let Some(message) = auto_splitter.last_log() {
+        content = content.push(iced::widget::text(format!("Last message: {message}")));
+    }
+
+    if app.settings.splits_path.is_none() {
+        content = content.push(iced::widget::text(
+            "Load or save a splits file to remember its auto splitter.",
+        ));
+    }
+
+    let user_settings = auto_splitter.user_settings();
+    if !user_settings.is_empty() {
+        content = content.push(
+            container(
+                column(user_settings.into_iter().map(|setting| {
+                    let key = setting.key;
+                    iced::widget::checkbox(setting.description, setting.value)
+                        .on_toggle(move |value| Message::SetAutoSplitterSetting(key.clone(), value))
+                        .into()
+                }))
+                .spacing(8.),
+            )
+            .padding(Padding::default().left(16.)),
+        );
+    }
+
+    content = content.push(
+        row![
+            iced::widget::button("Load...").on_press(Message::TryLoadAutoSplitter),
+            iced::widget::button("Unload").on_press(Message::UnloadAutoSplitter)
+        ]
+        .spacing(8.),
+    );
+
+    container(content)
+        .style(section_style)
+        .padding(Padding::default().left(16.0).top(8.).bottom(8.))
+        .width(Length::Fill)
+        .into()
+}
+
+fn server(app: &App) -> iced::Element<'_, Message> {
+    container(
+        column![
+            iced::widget::text("LiveSplit Server: "),
+            iced::widget::checkbox("Enabled", app.settings.server.enabled)
+                .on_toggle(Message::ToggleServer),
+            row![
+                iced::widget::text("Port"),
+                iced::widget::text_input("16834", &app.server_port_buffer)
+                    .on_input(Message::UpdateServerPort)
+                    .width(100.)
+                    .wrap_focus(|f| if f {
+                        Message::None
+                    } else {
+                        Message::ServerPortBlur
+                    })
+            ]
+            .spacing(8.)
+            .align_y(iced::alignment::Vertical::Center),
+            iced::widget::checkbox(
+                "Allow connections from other devices on the network",
+                app.settings.server.allow_lan
+            )
+            .on_toggle(Message::SetServerAllowLan),
+        ]
+        .spacing(8.),
+    )
+    .style(section_style)
+    .padding(Padding::default().left(16.0).top(8.).bottom(8.))
+    .width(Length::Fill)
+    .into()
+}
+
+fn websocket_server(app: &App) -> iced::Element<'_, Message> {
+    container(
+        column![
+            iced::widget::text("WebSocket Server: "),
+            iced::widget::checkbox("Enabled", app.settings.websocket.enabled)
+                .on_toggle(Message::ToggleWebSocketServer),
+            row![
+                iced::widget::text("Port"),
+                iced::widget::text_input("16835", &app.websocket_port_buffer)
+                    .on_input(Message::UpdateWebSocketPort)
+                    .width(100.)
+                    .wrap_focus(|f| if f {
+                        Message::None
+                    } else {
+                        Message::WebSocketPortBlur
+                    })
+            ]
+            .spacing(8.)
+            .align_y(iced::alignment::Vertical::Center),
+            iced::widget::checkbox(
+                "Allow connections from other devices on the network",
+                app.settings.websocket.allow_lan
+            )
+            .on_toggle(Message::SetWebSocketAllowLan),
+            row![
+                iced::widget::text("Allowed web pages"),
+                iced::widget::text_input("https://example.com, ...", &app.websocket_origins_buffer)
+                    .on_input(Message::UpdateWebSocketOrigins)
+                    .wrap_focus(|f| if f {
+                        Message::None
+                    } else {
+                        Message::WebSocketOriginsBlur
+                    })
+            ]
+            .spacing(8.)
+            .align_y(iced::alignment::Vertical::Center),
+            iced::widget::text(
+                "Pages served from this computer can always connect. Other websites need to be listed here."
+            ),
+        ]
+        .spacing(8.),
+    )
+    .style(section_style)
+    .padding(Padding::default().left(16.0).top(8.).bottom(8.))
+    .width(Length::Fill)
+    .into()
+}
+
+fn auto_save(app: &App) -> iced::Element<'_, Message> {
+    let auto_save = app.settings.auto_save;
+
+    container(
+        column![
+            iced::widget::text("Auto Save: "),
+            iced::widget::checkbox("Save splits when the timer is reset", auto_save.on_reset)
+                .on_toggle(Message::SetAutoSaveOnReset),
+            iced::widget::checkbox(
+                "Save splits when a new personal best is set",
+                auto_save.on_personal_best
+            )
+            .on_toggle(Message::SetAutoSaveOnPersonalBest),
+        ]
+        .spacing(8.),
+    )
+    .style(section_style)
+    .padding(Padding::default().left(16.0).top(8.).bottom(8.))
+    .width(Length::Fill)
+    .into()
+}
+
+fn backups(app: &App) -> iced::Element<'_, Message> {
+    let directory = app.settings.backup.directory().map_or_else(
+        || "No backup directory available".to_owned(),
+        |directory| directory.display().to_string(),
+    );
+
+    container(
+        column![
+            iced::widget::text("Backups: "),
+            row![
+                iced::widget::text(format!("Directory: {directory}")),
+                iced::widget::button("Choose").on_press(Message::TryChooseBackupDirectory),
+                iced::widget::button("Default").on_press_maybe(
+                    app.settings
+                        .backup
+                        .directory
+                        .is_some()
+                        .then_some(Message::SetBackupDirectory(None))
+                ),
+            ]
+            .spacing(8.)
+            .align_y(iced::alignment::Vertical::Center),
+            row![
+                iced::widget::text("Backups to keep per splits file"),
+                iced::widget::text_input("10", &app.max_backups_buffer)
+                    .on_input(Message::UpdateMaxBackups)
+                    .width(100.)
+                    .wrap_focus(|f| if f {
+                        Message::None
+                    } else {
+                        Message::MaxBackupsBlur
+                    })
+            ]
+            .spacing(8.)
+            .align_y(iced::alignment::Vertical::Center),
+            iced::widget::button("Restore Backup").on_press_maybe(
+                (!app.livesplit_state.is_timer_mid_run()).then_some(Message::TryRestoreBackup)
+            ),
+        ]
+        .spacing(8.),
+    )
+    .style(section_style)
+    .padding(Padding::default().left(16.0).top(8.).bottom(8.))
+    .width(Length::Fill)
+    .into()
+}
+
+fn section_style(theme: &iced::Theme) -> container::Style {
+    container::Style {
         border: Border {
             color: theme.palette().text,
             width: 1.,
             radius: Radius::default(),
         },
         ..Default::default()
-    })
-    .padding(Padding::default().left(16.0).top(8.).bottom(8.));
-    container(column![hotkeys,])
-        .padding(Padding::new(16.0))
-        .into()
+    }
 }
@@ -0,0 +1,60 @@
+use iced::{Length, Padding, alignment::Vertical};
+use iced_widget::{button, column, container, horizontal_space, row, scrollable, text, toggler};
+
+use crate::{
+    App,
+    state::sum_of_best_cleaner::Message,
+    ui::edit_splits_window::{even_row_style, odd_row_style},
+};
+
+pub fn view(app: &App) -> iced::Element<'_, crate::Message> {
+    let state = app
+        .sum_of_best_cleaner_state
+        .as_ref()
+        .expect("Tried to draw sum of best cleaner window with no cleaner");
+
+    let to_remove = state.entries.iter().filter(|e| e.remove).count();
+
+    let entries = column(state.entries.iter().enumerate().map(|(idx, entry)| {
+        container(
+            row![
+                text(&entry.explanation).width(Length::Fill),
+                toggler(entry.remove)
+                    .label("Remove")
+                    .on_toggle(move |remove| Message::SetRemove(idx, remove).into_app_message()),
+            ]
+            .spacing(16.)
+            .align_y(Vertical::Center),
+        )
+        .padding(8.)
+        .style(if idx.is_multiple_of(2) {
+            even_row_style
+        } else {
+            odd_row_style
+        })
+        .into()
+    }));
+
+    let buttons = row![
+        button("Remove All").on_press(Message::RemoveAll.into_app_message()),
+        button("Keep All").on_press(Message::KeepAll.into_app_message()),
+        horizontal_space(),
+        text(format!("{to_remove} of {} to remove", state.entries.len())),
+        button("Apply").on_press(crate::Message::ApplySumOfBestCleaner),
+        button("Cancel").on_press(crate::Message::CancelSumOfBestCleaner),
+    ]
+    .spacing(8.)
+    .align_y(Vertical::Center);
+
+    column![
+        text(
+            "Skipped splits can leave combined segment times in the history that are faster than the best segments \
+             they span. Choose which of these look wrong and should be removed."
+        ),
+        scrollable(entries).height(Length::Fill),
+        buttons
+    ]
+    .spacing(8.)
+    .padding(Padding::new(8.))
+    .into()
+}
@@ -0,0 +1,418 @@
+//! A WebSocket server for browser based overlays. Clients send JSON commands shaped like livesplit-core's
+//! `Command` type (`{"command": "splitOrStart"}`), and every client gets pushed a JSON event whenever the timer
+//! changes (`{"event": "splitted", "splitIndex": 0}`), no matter whether the change came from a hotkey, the auto
+//! splitter or another client.
+
+use std::time::Duration;
+
+use iced::{
+    Task,
+    futures::{SinkExt, StreamExt},
+};
+use livesplit_core::{SharedTimer, Timer, TimerPhase, TimingMethod};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::{broadcast, oneshot, watch},
+    task::JoinSet,
+};
+use tokio_tungstenite::tungstenite::{
+    Message as WsMessage,
+    handshake::server::{ErrorResponse, Request, Response as HandshakeResponse},
+    http::StatusCode,
+};
+
+use crate::{
+    Message,
+    app_settings::WebSocketSettings,
+    livesplit_state::{ResetRequest, set_game_time_paused},
+};
+
+/// How long to wait before accepting connections again after it failed.
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+pub struct WebSocketServer {
+    shutdown: oneshot::Sender<()>,
+    stopped: oneshot::Receiver<()>,
+}
+
+impl WebSocketServer {
+    /// Starts listening on the configured port. The returned task runs the server on iced's runtime until
+    /// `shutdown` is called.
+    pub fn start(
+        settings: &WebSocketSettings,
+        timer: SharedTimer,
+        reset_request: ResetRequest,
+    ) -> (Self, Task<Message>) {
+        let (shutdown, shutdown_receiver) = oneshot::channel();
+        let (stopped_sender, stopped) = oneshot::channel();
+
+        let task = Task::future(run(
+            settings.clone(),
+            timer,
+            reset_request,
+            shutdown_receiver,
+            stopped_sender,
+        ));
+
+        (Self { shutdown, stopped }, task)
+    }
+
+    /// Closes every connection and stops listening. The returned task finishes once that's done.
+    pub fn shutdown(self) -> Task<Message> {
+        self.shutdown.send(()).ok();
+
+        Task::future(async move {
+            // don't let a client that never acknowledges the close hold up quitting
+            tokio::time::timeout(Duration::from_secs(2), self.stopped)
+                .await
+                .ok();
+        })
+        .discard()
+    }
+}
+
+async fn run(
+    settings: WebSocketSettings,
+    timer: SharedTimer,
+    reset_request: ResetRequest,
+    mut shutdown: oneshot::Receiver<()>,
+    stopped: oneshot::Sender<()>,
+) -> Message {
+    let port = settings.port;
+    let address = if settings.allow_lan {
+        "0.0.0.0"
+    } else {
+        "127.0.0.1"
+    };
+    let listener = match TcpListener::bind((address, port)).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            stopped.send(()).ok();
+            return Message::ErrorOccurred {
+                title: "Failed to start WebSocket server".to_owned(),
+                error: format!("Could not listen on port {port}: {e}"),
+            };
+        }
+    };
+
+    let (events, _) = broadcast::channel(64);
+    let (close, close_receiver) = watch::channel(false);
+    let mut connections = JoinSet::new();
+
+    // Nothing notifies us when the hotkey system or the auto splitter touches the timer, so watch it instead.
+    let mut last_status = TimerStatus::capture(&timer);
+    let mut watch_interval = tokio::time::interval(Duration::from_millis(16));
+
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => break,
+            accepted = listener.accept() => {
+                match accepted {
+                    Ok((stream, _)) => {
+                        connections.spawn(handle_connection(
+                            stream,
+                            settings.allowed_origins.clone(),
+                            timer.clone(),
+                            reset_request.clone(),
+                            events.subscribe(),
+                            close_receiver.clone(),
+                        ));
+                    }
+                    // usually running out of file descriptors, which won't clear up if we retry straight away
+                    Err(e) => {
+                        eprintln!("WebSocket server failed to accept a connection: {e}");
+                        tokio::time::sleep(ACCEPT_RETRY_DELAY).await;
+                    }
+                }
+            }
+            _ = watch_interval.tick() => {
+                let status = TimerStatus::capture(&timer);
+                for event in last_status.events_until(&status) {
+                    // no subscribers is fine
+                    events.send(event).ok();
+                }
+                last_status = status;
+            }
+            Some(_) = connections.join_next() => {}
+        }
+    }
+
+    close.send(true).ok();
+    while connections.join_next().await.is_some() {}
+    stopped.send(()).ok();
+
+    Message::None
+}
+
+async fn handle_connection(
+    stream: TcpStream,
+    allowed_origins: Vec<String>,
+    timer: SharedTimer,
+    reset_request: ResetRequest,
+    mut events: broadcast::Receiver<Event>,
+    mut close: watch::Receiver<bool>,
+) {
+    // Browsers let any page open a WebSocket to any address, so a website the user happens to visit could drive the
+    // timer. Browsers always say which page is connecting, other clients don't send an origin at all.
+    #[allow(clippy::result_large_err)] // the signature is tungstenite's
+    let check_origin = |request: &Request, response: HandshakeResponse| {
+        let origin = request
+            .headers()
+            .get("Origin")
+            .map(|origin| origin.to_str().unwrap_or_default());
+        match origin {
+            Some(origin) if !is_origin_allowed(origin, &allowed_origins) => {
+                let mut rejection = ErrorResponse::new(Some(format!(
+                    "{origin} isn't allowed to connect to LiveSplit"
+                )));
+                *rejection.status_mut() = StatusCode::FORBIDDEN;
+                Err(rejection)
+            }
+            _ => Ok(response),
+        }
+    };
+
+    let Ok(websocket) = tokio_tungstenite::accept_hdr_async(stream, check_origin).await else {
+        return;
+    };
+    let (mut sink, mut source) = websocket.split();
+
+    loop {
+        let outgoing = tokio::select! {
+            incoming = source.next() => match incoming {
+                Some(Ok(WsMessage::Text(text))) => {
+                    let response = match serde_json::from_str::<Command>(&text) {
+                        Ok(command) => {
+                            command.execute(
+                                &mut timer.write().expect("Timer lock poisoned!"),
+                                &reset_request,
+                            )
+                        }
+                        Err(e) => Response::Error(e.to_string()),
+                    };
+                    serde_json::to_string(&response)
+                }
+                Some(Ok(WsMessage::Close(_)) | Err(_)) | None => return,
+                Some(Ok(_)) => continue,
+            },
+            event = events.recv() => match event {
+                Ok(event) => serde_json::to_string(&event),
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+            _ = close.changed() => {
+                sink.send(WsMessage::Close(None)).await.ok();
+                return;
+            }
+        };
+
+        let Ok(json) = outgoing else {
+            continue;
+        };
+        if sink.send(WsMessage::Text(json.into())).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Pages served from this machine, OBS browser sources showing local files and anything the user allowed by hand.
+fn is_origin_allowed(origin: &str, allowed_origins: &[String]) -> bool {
+    if allowed_origins
+        .iter()
+        .any(|allowed| allowed.trim_end_matches('/').eq_ignore_ascii_case(origin))
+    {
+        return true;
+    }
+
+    let Some((scheme, authority)) = origin.split_once("://") else {
+        return false;
+    };
+    // OBS serves local files from this made up host
+    if authority == "absolute" {
+        return true;
+    }
+
+    let host = match authority.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next(),
+        None => authority.split(':').next(),
+    };
+    matches!(scheme, "http" | "https") && matches!(host, Some("localhost" | "127.0.0.1" | "::1"))
+}
+
+#[derive(Deserialize)]
+#[serde(
+    tag = "command",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+enum Command {
+    SplitOrStart,
+    Start,
+    Split,
+    UndoSplit,
+    SkipSplit,
+    Reset { save_attempt: Option<bool> },
+    Pause,
+    Resume,
+    TogglePauseOrStart,
+    UndoAllPauses,
+    SwitchToPreviousComparison,
+    SwitchToNextComparison,
+    SetCurrentComparison { comparison: String },
+    ToggleTimingMethod,
+    SetCurrentTimingMethod { timing_method: TimingMethod },
+    InitializeGameTime,
+    SetGameTime { time: String },
+    PauseGameTime,
+    ResumeGameTime,
+    SetLoadingTimes { time: String },
+    SetCustomVariable { key: String, value: String },
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+enum Response {
+    Success,
+    Error(String),
+}
+
+impl Command {
+    fn execute(self, timer: &mut Timer, reset_request: &ResetRequest) -> Response {
+        match self {
+            Self::SplitOrStart => timer.split_or_start(),
+            Self::Start => timer.start(),
+            Self::Split => timer.split(),
+            Self::UndoSplit => timer.undo_split(),
+            Self::SkipSplit => timer.skip_split(),
+            // a client that says what to do with the attempt gets that, everyone else goes through the app so it
+            // can ask whether to update the splits
+            Self::Reset {
+                save_attempt: Some(save_attempt),
+            } => timer.reset(save_attempt),
+            Self::Reset { save_attempt: None } => reset_request.request(),
+            Self::Pause => timer.pause(),
+            Self::Resume => timer.resume(),
+            Self::TogglePauseOrStart => timer.toggle_pause_or_start(),
+            Self::UndoAllPauses => timer.undo_all_pauses(),
+            Self::SwitchToPreviousComparison => timer.switch_to_previous_comparison(),
+            Self::SwitchToNextComparison => timer.switch_to_next_comparison(),
+            Self::SetCurrentComparison { comparison } => {
+                if timer.set_current_comparison(comparison).is_err() {
+                    return Response::Error("Comparison does not exist".to_owned());
+                }
+            }
+            Self::ToggleTimingMethod => timer.toggle_timing_method(),
+            Self::SetCurrentTimingMethod { timing_method } => {
+                timer.set_current_timing_method(timing_method);
+            }
+            Self::InitializeGameTime => timer.initialize_game_time(),
+            Self::SetGameTime { time } => match time.parse() {
+                Ok(time) => timer.set_game_time(time),
+                Err(e) => return Response::Error(e.to_string()),
+            },
+            Self::PauseGameTime => set_game_time_paused(timer, true),
+            Self::ResumeGameTime => set_game_time_paused(timer, false),
+            Self::SetLoadingTimes { time } => match time.parse() {
+                Ok(time) => timer.set_loading_times(time),
+                Err(e) => return Response::Error(e.to_string()),
+            },
+            Self::SetCustomVariable { key, value } => timer.set_custom_variable(key, value),
+        }
+        Response::Success
+    }
+}
+
+#[derive(Clone, Serialize)]
+#[serde(
+    tag = "event",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+enum Event {
+    Started,
+    Splitted { split_index: usize },
+    SplitSkipped { split_index: usize },
+    SplitUndone { split_index: usize },
+    Finished,
+    Reset,
+    Paused,
+    Resumed,
+    ComparisonChanged { comparison: String },
+    TimingMethodChanged { timing_method: TimingMethod },
+}
+
+/// The parts of the timer's state we turn into events.
+struct TimerStatus {
+    phase: TimerPhase,
+    split_index: Option<usize>,
+    // whether each segment has a split time, so a skip can be told apart from a split
+    split: Vec<bool>,
+    comparison: String,
+    timing_method: TimingMethod,
+}
+
+impl TimerStatus {
+    fn capture(timer: &SharedTimer) -> Self {
+        let timer = timer.read().expect("Timer lock poisoned!");
+        let method = timer.current_timing_method();
+
+        Self {
+            phase: timer.current_phase(),
+            split_index: timer.current_split_index(),
+            split: timer
+                .run()
+                .segments()
+                .iter()
+                .map(|s| s.split_time()[method].is_some())
+                .collect(),
+            comparison: timer.current_comparison().to_owned(),
+            timing_method: method,
+        }
+    }
+
+    fn events_until(&self, now: &Self) -> Vec<Event> {
+        use TimerPhase::{Ended, NotRunning, Paused, Running};
+
+        let mut events = vec![];
+
+        match (self.phase, now.phase) {
+            (NotRunning, Running) => events.push(Event::Started),
+            (Running | Paused | Ended, NotRunning) => events.push(Event::Reset),
+            (Running, Paused) => events.push(Event::Paused),
+            (Paused, Running) => events.push(Event::Resumed),
+            _ => {}
+        }
+
+        if let (Some(before), Some(after)) = (self.split_index, now.split_index) {
+            for split_index in before..after {
+                if now.split.get(split_index).copied().unwrap_or_default() {
+                    events.push(Event::Splitted { split_index });
+                } else {
+                    events.push(Event::SplitSkipped { split_index });
+                }
+            }
+            for split_index in (after..before).rev() {
+                events.push(Event::SplitUndone { split_index });
+            }
+        }
+
+        if self.phase != Ended && now.phase == Ended {
+            events.push(Event::Finished);
+        }
+
+        if self.comparison != now.comparison {
+            events.push(Event::ComparisonChanged {
+                comparison: now.comparison.clone(),
+            });
+        }
+
+        if self.timing_method != now.timing_method {
+            events.push(Event::TimingMethodChanged {
+                timing_method: now.timing_method,
+            });
+        }
+
+        events
+    }
+}